@@ -0,0 +1,303 @@
+use std::time::Duration;
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*, window::PrimaryWindow};
+use rand::Rng;
+
+use crate::{
+    collision::{Collidable, Faction},
+    random::{RandomStream, StreamLabel},
+    steering::{Behavior, Obstacle, Steering, SteeringSystemLabel},
+    Bullet, Weapon, WeaponTarget,
+};
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (think_system, act_system)
+                .chain()
+                .before(SteeringSystemLabel)
+                .in_set(AiSystemLabel),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct AiSystemLabel;
+
+/// Something an AI can do. Movement goes through the entity's [`Steering`], attacks through its
+/// [`Weapon`] and [`WeaponTarget`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Task {
+    /// Holds fire and lets the steering carry on.
+    Wait,
+    MoveTo(Waypoint),
+    /// Closes in on the closest hostile entity and fires at it.
+    Attack,
+    /// Steers away from the closest threat.
+    Evade,
+    /// Heads for the nearest side edge to leave the arena.
+    FleeOffScreen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waypoint {
+    Point(Vec2),
+    /// A point anywhere in the arena, picked when the task is chosen.
+    Random,
+}
+
+/// Scores how much a task is worth doing right now, from `0.0` to `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Consideration {
+    Fixed(f32),
+    /// Whether a hostile entity is within the radius.
+    HostileWithin(f32),
+    /// Whether no hostile entity is within the radius.
+    NoHostileWithin(f32),
+    /// Grows as the closest obstacle or hostile bullet gets nearer than the radius.
+    ThreatWithin(f32),
+    /// Whether the entity has been around for longer than the given number of seconds.
+    OlderThan(f32),
+}
+
+/// A task with the considerations that score it. The score is the product of all of them, times
+/// the weight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Action {
+    pub task: Task,
+    pub considerations: Vec<Consideration>,
+    pub weight: f32,
+}
+
+impl Action {
+    pub fn new(task: Task, weight: f32) -> Self {
+        Self {
+            task,
+            considerations: Vec::new(),
+            weight,
+        }
+    }
+
+    pub fn when(mut self, consideration: Consideration) -> Self {
+        self.considerations.push(consideration);
+        self
+    }
+}
+
+/// Utility AI that regularly picks the best scoring of its actions and carries it out until the
+/// next decision.
+#[derive(Debug, Clone, Component)]
+pub struct Brain {
+    actions: Vec<Action>,
+    think: Timer,
+    age: f32,
+    current: Option<Task>,
+}
+
+impl Brain {
+    pub fn new(actions: Vec<Action>, think_interval: Duration) -> Self {
+        Self {
+            actions,
+            think: Timer::new(think_interval, TimerMode::Repeating),
+            age: 0.0,
+            current: None,
+        }
+    }
+}
+
+struct AiWaypoint;
+
+impl StreamLabel for AiWaypoint {
+    const LABEL: &'static str = "ai_waypoint";
+}
+
+/// What the AI knows about its surroundings on a given frame.
+struct Senses {
+    hostile: Option<(Entity, f32)>,
+    threat: Option<(Entity, f32)>,
+}
+
+fn sense(
+    entity: Entity,
+    position: Vec2,
+    faction: Option<&Faction>,
+    hostiles: &Query<(Entity, &Transform, &Faction), (With<Collidable>, Without<Bullet>)>,
+    threats: &Query<(Entity, &Transform, Option<&Faction>), Or<(With<Obstacle>, With<Bullet>)>>,
+) -> Senses {
+    let closest = |iter: &mut dyn Iterator<Item = (Entity, Vec2)>| {
+        iter.map(|(other, at)| (other, at.distance(position)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    };
+
+    let hostile = closest(
+        &mut hostiles
+            .iter()
+            .filter(|(other, _, other_faction)| *other != entity && Some(*other_faction) != faction)
+            .map(|(other, transform, _)| (other, transform.translation.truncate())),
+    );
+
+    let threat = closest(
+        &mut threats
+            .iter()
+            .filter(|(other, _, other_faction)| {
+                *other != entity && (other_faction.is_none() || *other_faction != faction)
+            })
+            .map(|(other, transform, _)| (other, transform.translation.truncate())),
+    );
+
+    Senses { hostile, threat }
+}
+
+impl Consideration {
+    fn score(&self, senses: &Senses, age: f32) -> f32 {
+        match *self {
+            Consideration::Fixed(score) => score,
+            Consideration::HostileWithin(radius) => {
+                let near = senses
+                    .hostile
+                    .is_some_and(|(_, distance)| distance < radius);
+                if near {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Consideration::NoHostileWithin(radius) => {
+                1.0 - Consideration::HostileWithin(radius).score(senses, age)
+            }
+            Consideration::ThreatWithin(radius) => senses
+                .threat
+                .map_or(0.0, |(_, distance)| (1.0 - distance / radius).max(0.0)),
+            Consideration::OlderThan(seconds) => {
+                if age >= seconds {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+fn think_system(
+    fixed_time: Res<FixedTime>,
+    mut query: Query<(Entity, &mut Brain, &Transform, Option<&Faction>)>,
+    hostiles: Query<(Entity, &Transform, &Faction), (With<Collidable>, Without<Bullet>)>,
+    threats: Query<(Entity, &Transform, Option<&Faction>), Or<(With<Obstacle>, With<Bullet>)>>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut rng: Local<RandomStream<AiWaypoint>>,
+) {
+    for (entity, mut brain, transform, faction) in query.iter_mut() {
+        brain.age += fixed_time.period.as_secs_f32();
+        brain.think.tick(fixed_time.period);
+
+        if brain.current.is_some() && !brain.think.just_finished() {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let senses = sense(entity, position, faction, &hostiles, &threats);
+        let age = brain.age;
+
+        let best = brain
+            .actions
+            .iter()
+            .map(|action| {
+                let score = action
+                    .considerations
+                    .iter()
+                    .fold(action.weight, |score, consideration| {
+                        score * consideration.score(&senses, age)
+                    });
+                (action.task, score)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(Task::Wait, |(task, _)| task);
+
+        // Keep heading to the same random point rather than picking a new one on every decision.
+        let unchanged = matches!(
+            (brain.current, best),
+            (Some(Task::MoveTo(_)), Task::MoveTo(Waypoint::Random))
+        );
+        if unchanged {
+            continue;
+        }
+
+        brain.current = Some(match best {
+            Task::MoveTo(Waypoint::Random) => {
+                let arena = primary_window.get_single().map_or(Vec2::ZERO, |window| {
+                    Vec2::new(window.width(), window.height()) / 2.0
+                });
+                let point = Vec2::new(
+                    rng.gen_range(-arena.x..=arena.x),
+                    rng.gen_range(-arena.y..=arena.y),
+                );
+                Task::MoveTo(Waypoint::Point(point))
+            }
+            task => task,
+        });
+    }
+}
+
+fn act_system(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &Brain,
+        &Transform,
+        Option<&Faction>,
+        Option<&mut Steering>,
+        Option<&mut Weapon>,
+    )>,
+    hostiles: Query<(Entity, &Transform, &Faction), (With<Collidable>, Without<Bullet>)>,
+    threats: Query<(Entity, &Transform, Option<&Faction>), Or<(With<Obstacle>, With<Bullet>)>>,
+) {
+    for (entity, brain, transform, faction, steering, weapon) in query.iter_mut() {
+        let Some(task) = brain.current else {
+            continue;
+        };
+
+        let position = transform.translation.truncate();
+        let senses = sense(entity, position, faction, &hostiles, &threats);
+
+        let goal = match task {
+            Task::Wait => None,
+            Task::Attack => senses.hostile.map(|(hostile, _)| Behavior::Seek(hostile)),
+            Task::MoveTo(Waypoint::Point(point)) => Some(Behavior::MoveTo(point)),
+            Task::MoveTo(Waypoint::Random) => None,
+            Task::Evade => senses.threat.map(|(threat, distance)| Behavior::Flee {
+                target: threat,
+                radius: distance + 1.0,
+            }),
+            Task::FleeOffScreen => {
+                let side = if position.x < 0.0 { -1.0 } else { 1.0 };
+                let speed = steering
+                    .as_ref()
+                    .map_or(0.0, |steering| steering.max_speed());
+                Some(Behavior::Cruise(Vec2::X * side * speed))
+            }
+        };
+
+        if let Some(mut steering) = steering {
+            steering.set_goal(goal);
+        }
+
+        let target = match task {
+            Task::Attack => senses.hostile.map(|(hostile, _)| hostile),
+            _ => None,
+        };
+
+        if let Some(mut weapon) = weapon {
+            weapon.triggered = target.is_some();
+        }
+
+        match target {
+            Some(target) => commands.entity(entity).insert(WeaponTarget(target)),
+            None => commands.entity(entity).remove::<WeaponTarget>(),
+        };
+    }
+}
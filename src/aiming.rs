@@ -0,0 +1,208 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+/// How a weapon picks the direction of its shots from its target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AimStrategy {
+    /// Ignores the target and fires in any direction.
+    Random,
+    /// Fires at where the target is now.
+    Direct,
+    /// Fires at where the target will be when the shot reaches it. With `wrap`, the target's
+    /// images across the arena edges are considered too, for shots that wrap around.
+    Lead { wrap: bool },
+}
+
+/// Aiming of a weapon, applied to its shots before they are fired.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Aim {
+    pub strategy: AimStrategy,
+    /// Half angle of the cone the shots are scattered in, in radians.
+    pub inaccuracy: f32,
+}
+
+impl Default for Aim {
+    fn default() -> Self {
+        Self {
+            strategy: AimStrategy::Direct,
+            inaccuracy: 0.0,
+        }
+    }
+}
+
+impl Aim {
+    pub fn random() -> Self {
+        Self {
+            strategy: AimStrategy::Random,
+            inaccuracy: 0.0,
+        }
+    }
+
+    pub fn lead() -> Self {
+        Self {
+            strategy: AimStrategy::Lead { wrap: false },
+            inaccuracy: 0.0,
+        }
+    }
+
+    /// Lets a leading aim shoot across the arena edges, for projectiles that wrap around.
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        if let AimStrategy::Lead { wrap: lead_wrap } = &mut self.strategy {
+            *lead_wrap = wrap;
+        }
+        self
+    }
+
+    pub fn with_inaccuracy(mut self, inaccuracy: f32) -> Self {
+        self.inaccuracy = inaccuracy;
+        self
+    }
+
+    /// Direction to fire in, or `None` when the target cannot be reached and the caller should
+    /// fall back to its own heading.
+    pub fn direction(
+        &self,
+        rng: &mut impl Rng,
+        shot: Shot,
+        target: Option<Target>,
+        arena: Vec2,
+    ) -> Option<Vec2> {
+        let direction = match (self.strategy, target) {
+            (AimStrategy::Random, _) => Some(Vec2::from_angle(rng.gen_range(0.0..2.0 * PI))),
+            (_, None) => None,
+            (AimStrategy::Direct, Some(target)) => (target.position - shot.origin).try_normalize(),
+            (AimStrategy::Lead { wrap }, Some(target)) => wrapped_intercept(
+                target.position - shot.origin,
+                target.velocity - shot.inherited_velocity,
+                shot.speed,
+                wrap.then_some(arena),
+            )
+            .map(|(_, direction)| direction)
+            .or_else(|| (target.position - shot.origin).try_normalize()),
+        }?;
+
+        if self.inaccuracy > 0.0 {
+            let error = rng.gen_range(-self.inaccuracy..self.inaccuracy);
+            Some(Vec2::from_angle(error).rotate(direction))
+        } else {
+            Some(direction)
+        }
+    }
+}
+
+/// The projectile about to be fired.
+#[derive(Debug, Clone, Copy)]
+pub struct Shot {
+    pub origin: Vec2,
+    pub speed: f32,
+    /// Velocity of the shooter carried over by the projectile, if any.
+    pub inherited_velocity: Vec2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Target {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Solves `|offset + velocity * t| = speed * t` for the earliest positive `t`, returning it with
+/// the direction to fire in.
+pub fn intercept(offset: Vec2, velocity: Vec2, speed: f32) -> Option<(f32, Vec2)> {
+    let a = velocity.length_squared() - speed * speed;
+    let b = 2.0 * offset.dot(velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        (b < 0.0).then(|| -c / b)?
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+            .into_iter()
+            .filter(|t| *t > 0.0)
+            .min_by(f32::total_cmp)?
+    };
+
+    let direction = (offset + velocity * time).try_normalize()?;
+    Some((time, direction))
+}
+
+/// Same as [`intercept`], but with an `arena` also trying the target's images across its edges
+/// and keeping the earliest hit.
+pub fn wrapped_intercept(
+    offset: Vec2,
+    velocity: Vec2,
+    speed: f32,
+    arena: Option<Vec2>,
+) -> Option<(f32, Vec2)> {
+    let offsets = arena.map_or_else(|| vec![Vec2::ZERO], wrap_offsets);
+    offsets
+        .into_iter()
+        .filter_map(|image| intercept(offset + image, velocity, speed))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+}
+
+fn wrap_offsets(arena: Vec2) -> Vec<Vec2> {
+    [-1.0, 0.0, 1.0]
+        .into_iter()
+        .flat_map(|x| [-1.0, 0.0, 1.0].map(|y| Vec2::new(x, y) * arena))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intercept_stationary_target() {
+        let (time, direction) = intercept(Vec2::new(300.0, 0.0), Vec2::ZERO, 100.0).unwrap();
+        assert!((time - 3.0).abs() < 1e-4);
+        assert!(direction.abs_diff_eq(Vec2::X, 1e-4));
+    }
+
+    #[test]
+    fn intercept_target_outrunning_the_shot() {
+        assert!(intercept(Vec2::new(100.0, 0.0), Vec2::new(200.0, 0.0), 100.0).is_none());
+    }
+
+    #[test]
+    fn intercept_target_as_fast_as_the_shot() {
+        // Coming straight at the shooter, the shot meets the target halfway.
+        let (time, direction) =
+            intercept(Vec2::new(200.0, 0.0), Vec2::new(-100.0, 0.0), 100.0).unwrap();
+        assert!((time - 1.0).abs() < 1e-4);
+        assert!(direction.abs_diff_eq(Vec2::X, 1e-4));
+
+        // Moving away, it is never caught.
+        assert!(intercept(Vec2::new(200.0, 0.0), Vec2::new(100.0, 0.0), 100.0).is_none());
+    }
+
+    #[test]
+    fn intercept_crossing_target() {
+        // 3-4-5 triangle: the target moves 300 up while the shot covers 500.
+        let (time, direction) =
+            intercept(Vec2::new(400.0, 0.0), Vec2::new(0.0, 300.0), 500.0).unwrap();
+        assert!((time - 1.0).abs() < 1e-4);
+        assert!(direction.abs_diff_eq(Vec2::new(0.8, 0.6), 1e-4));
+    }
+
+    #[test]
+    fn intercept_across_the_arena_edge() {
+        // The target is 700 to the right but only 100 to the left through the edge.
+        let arena = Vec2::new(800.0, 600.0);
+        let offset = Vec2::new(700.0, 0.0);
+
+        let (time, direction) = wrapped_intercept(offset, Vec2::ZERO, 100.0, None).unwrap();
+        assert!((time - 7.0).abs() < 1e-4);
+        assert!(direction.abs_diff_eq(Vec2::X, 1e-4));
+
+        let (time, direction) = wrapped_intercept(offset, Vec2::ZERO, 100.0, Some(arena)).unwrap();
+        assert!((time - 1.0).abs() < 1e-4);
+        assert!(direction.abs_diff_eq(Vec2::NEG_X, 1e-4));
+    }
+}
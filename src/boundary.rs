@@ -1,10 +1,17 @@
 use bevy::{prelude::*, window::PrimaryWindow};
 
+use crate::{collision::CollisionSystemLabel, physics::PhysicsSystemLabel};
+
 pub struct BoundaryPlugin;
 
 impl Plugin for BoundaryPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostUpdate, (boundary_remove_system, boundary_wrap_system));
+        app.add_systems(
+            FixedUpdate,
+            (boundary_remove_system, boundary_wrap_system)
+                .after(PhysicsSystemLabel)
+                .before(CollisionSystemLabel),
+        );
     }
 }
 
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use bevy::{app::RunFixedUpdateLoop, prelude::*, time::TimeSystem};
+
+/// Must be added after the `TimePlugin`, whose fixed update loop it replaces.
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>()
+            .add_schedule(RunFixedUpdateLoop, Schedule::new())
+            .add_systems(First, clock_system.after(TimeSystem))
+            .add_systems(RunFixedUpdateLoop, fixed_update_system);
+    }
+}
+
+/// Gameplay time, which unlike the real [`Time`] can be paused, scaled and advanced one fixed
+/// step at a time. The fixed update schedule follows it instead of the real time, so the whole
+/// simulation freezes along with the effects timed here while the rest of the app keeps going.
+#[derive(Debug, Resource)]
+pub struct GameClock {
+    delta: Duration,
+    /// Debug pause, which single steps can advance.
+    paused: bool,
+    /// Pause of the in-game menu, kept apart so closing the menu leaves a debug pause alone.
+    menu_paused: bool,
+    scale: f32,
+    steps: u32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self {
+            delta: Duration::ZERO,
+            paused: false,
+            menu_paused: false,
+            scale: 1.0,
+            steps: 0,
+        }
+    }
+}
+
+impl GameClock {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.steps = 0;
+    }
+
+    pub fn set_menu_paused(&mut self, paused: bool) {
+        self.menu_paused = paused;
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(0.0, 16.0);
+    }
+
+    /// Advances a paused clock by a single fixed time step on the next frame.
+    pub fn step(&mut self) {
+        if self.paused {
+            self.steps += 1;
+        }
+    }
+}
+
+fn clock_system(time: Res<Time>, fixed_time: Res<FixedTime>, mut clock: ResMut<GameClock>) {
+    clock.delta = if clock.menu_paused {
+        Duration::ZERO
+    } else if !clock.paused {
+        time.delta().mul_f32(clock.scale)
+    } else if clock.steps > 0 {
+        clock.steps -= 1;
+        fixed_time.period
+    } else {
+        Duration::ZERO
+    };
+}
+
+/// Same as Bevy's own fixed update loop, but advancing by the [`GameClock`] rather than by the
+/// real [`Time`].
+fn fixed_update_system(world: &mut World) {
+    let delta = world.resource::<GameClock>().delta;
+    world.resource_mut::<FixedTime>().tick(delta);
+
+    let _ = world.try_schedule_scope(FixedUpdate, |world, schedule| {
+        while world.resource_mut::<FixedTime>().expend().is_ok() {
+            schedule.run(world);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::time::{TimePlugin, TimeUpdateStrategy};
+
+    use super::*;
+
+    #[derive(Default, Resource)]
+    struct Ticks(u32);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((TimePlugin, ClockPlugin))
+            .insert_resource(FixedTime::new(Duration::from_millis(10)))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                10,
+            )))
+            .init_resource::<Ticks>()
+            .add_systems(FixedUpdate, |mut ticks: ResMut<Ticks>| ticks.0 += 1);
+        // The very first update has no delta yet.
+        app.update();
+        app
+    }
+
+    fn ticks(app: &App) -> u32 {
+        app.world.resource::<Ticks>().0
+    }
+
+    #[test]
+    fn fixed_update_follows_the_clock() {
+        let mut app = app();
+        app.update();
+        app.update();
+        assert_eq!(ticks(&app), 2);
+
+        app.world.resource_mut::<GameClock>().set_scale(2.0);
+        app.update();
+        assert_eq!(ticks(&app), 4);
+    }
+
+    #[test]
+    fn pausing_leaves_the_real_time_running() {
+        let mut app = app();
+        app.world.resource_mut::<GameClock>().toggle_pause();
+        let elapsed = app.world.resource::<Time>().elapsed();
+
+        app.update();
+        app.update();
+        assert_eq!(ticks(&app), 0);
+        assert!(app.world.resource::<Time>().elapsed() > elapsed);
+
+        app.world.resource_mut::<GameClock>().step();
+        app.update();
+        app.update();
+        assert_eq!(ticks(&app), 1);
+    }
+
+    #[test]
+    fn closing_the_menu_keeps_the_debug_pause() {
+        let mut app = app();
+        let mut clock = app.world.resource_mut::<GameClock>();
+        clock.toggle_pause();
+        clock.set_menu_paused(true);
+        clock.set_menu_paused(false);
+
+        app.update();
+        assert_eq!(ticks(&app), 0);
+    }
+}
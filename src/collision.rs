@@ -5,22 +5,30 @@ use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 use crate::boundary::Bounding;
 
 pub struct CollisionPlugin<Hittable, Hurtable> {
+    friendly_fire: bool,
     _phantom: PhantomData<(Hittable, Hurtable)>,
 }
 
 impl<Hittable: Component, Hurtable: Component> CollisionPlugin<Hittable, Hurtable> {
     pub fn new() -> Self {
         Self {
+            friendly_fire: true,
             _phantom: PhantomData,
         }
     }
+
+    /// Ignores contacts between entities of the same [`Faction`].
+    pub fn without_friendly_fire(mut self) -> Self {
+        self.friendly_fire = false;
+        self
+    }
 }
 
 impl<Hittable: Component, Hurtable: Component> Plugin for CollisionPlugin<Hittable, Hurtable> {
     fn build(&self, app: &mut App) {
         app.add_event::<HitEvent<Hittable, Hurtable>>().add_systems(
-            Update,
-            collision_system::<Hittable, Hurtable>.in_set(CollisionSystemLabel),
+            FixedUpdate,
+            collision_system::<Hittable, Hurtable>(self.friendly_fire).in_set(CollisionSystemLabel),
         );
     }
 }
@@ -31,6 +39,7 @@ pub struct CollisionSystemLabel;
 #[derive(Debug, Event)]
 pub struct HitEvent<A, B> {
     entities: (Entity, Entity),
+    shooter: Option<Entity>,
     _phantom: PhantomData<(A, B)>,
 }
 
@@ -42,24 +51,63 @@ impl<A, B> HitEvent<A, B> {
     pub fn hurtable(&self) -> Entity {
         self.entities.1
     }
+
+    /// The [`Owner`] of the hittable, when it was fired by someone.
+    pub fn shooter(&self) -> Option<Entity> {
+        self.shooter
+    }
 }
 
 #[derive(Debug, Component)]
 pub struct Collidable;
 
+/// Entity that fired a projectile.
+#[derive(Debug, Clone, Copy, Component, Deref)]
+pub struct Owner(pub Entity);
+
+/// Side an entity fights on, used to filter out friendly fire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
 fn collision_system<A: Component, B: Component>(
-    mut hits: EventWriter<HitEvent<A, B>>,
-    hittables: Query<(Entity, &Transform, &Bounding), (With<Collidable>, With<A>)>,
-    hurtables: Query<(Entity, &Transform, &Bounding), (With<Collidable>, With<B>)>,
+    friendly_fire: bool,
+) -> impl FnMut(
+    EventWriter<HitEvent<A, B>>,
+    Query<
+        (
+            Entity,
+            &Transform,
+            &Bounding,
+            Option<&Faction>,
+            Option<&Owner>,
+        ),
+        (With<Collidable>, With<A>),
+    >,
+    Query<(Entity, &Transform, &Bounding, Option<&Faction>), (With<Collidable>, With<B>)>,
 ) {
-    for (hittable_entity, hit_transform, hit_bounds) in hittables.iter() {
-        for (hurtable_entity, hurt_transform, hurt_bounds) in hurtables.iter() {
-            let distance = (hit_transform.translation - hurt_transform.translation).length();
-            if distance < **hit_bounds + **hurt_bounds {
-                hits.send(HitEvent {
-                    entities: (hittable_entity, hurtable_entity),
-                    _phantom: PhantomData,
-                });
+    move |mut hits, hittables, hurtables| {
+        for (hittable_entity, hit_transform, hit_bounds, hit_faction, owner) in hittables.iter() {
+            for (hurtable_entity, hurt_transform, hurt_bounds, hurt_faction) in hurtables.iter() {
+                if !friendly_fire && hit_faction.is_some() && hit_faction == hurt_faction {
+                    continue;
+                }
+
+                // Nothing is ever hit by its own shots.
+                if owner.is_some_and(|owner| **owner == hurtable_entity) {
+                    continue;
+                }
+
+                let distance = (hit_transform.translation - hurt_transform.translation).length();
+                if distance < **hit_bounds + **hurt_bounds {
+                    hits.send(HitEvent {
+                        entities: (hittable_entity, hurtable_entity),
+                        shooter: owner.map(|owner| **owner),
+                        _phantom: PhantomData,
+                    });
+                }
             }
         }
     }
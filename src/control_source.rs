@@ -0,0 +1,210 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+
+use crate::{
+    aiming::intercept,
+    collision::{Collidable, Faction},
+    controls::{Control, ControlFrame, Controls, ControlsLabel},
+    physics::{TimeStep, Velocity},
+    players::Player,
+    replay::Replay,
+    Asteroid, Ship, SteeringControl, Ufo, Weapon,
+};
+
+pub struct ControlSourcePlugin;
+
+impl Plugin for ControlSourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemoteCommands>().add_systems(
+            FixedUpdate,
+            (source_commands_system, bot_commands_system)
+                .after(ControlsLabel)
+                .in_set(ControlSourceLabel),
+        );
+    }
+}
+
+/// Systems that fill the [`ShipCommands`] of every entity for the current fixed tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct ControlSourceLabel;
+
+/// What drives a ship.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub enum ControlSource {
+    /// The bindings of a player on this machine.
+    Local(Player),
+    /// The computer, chasing the closest target and shooting it.
+    Bot,
+    /// A recorded stream of the playing [`Replay`].
+    Replay(usize),
+    /// A peer over the network, through [`RemoteCommands`].
+    #[allow(dead_code)] // Seam for the network layer, which does not exist yet.
+    Remote(PeerId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PeerId(pub u64);
+
+/// Ship controls last received from every remote peer, filled in by the network layer.
+#[derive(Debug, Default, Resource)]
+pub struct RemoteCommands(HashMap<PeerId, ControlFrame>);
+
+impl RemoteCommands {
+    #[allow(dead_code)] // Called by the network layer, which does not exist yet.
+    pub fn set(&mut self, peer: PeerId, frame: ControlFrame) {
+        self.0.insert(peer, frame);
+    }
+
+    fn frame(&self, peer: PeerId) -> ControlFrame {
+        self.0.get(&peer).copied().unwrap_or_default()
+    }
+}
+
+/// Controls of a single ship for the current fixed tick, the only input its engine, steering and
+/// weapon listen to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Component)]
+pub struct ShipCommands {
+    current: ControlFrame,
+    previous: ControlFrame,
+}
+
+impl ShipCommands {
+    pub fn frame(&self) -> ControlFrame {
+        self.current
+    }
+
+    pub fn pressed(&self, control: Control) -> bool {
+        self.current.bits & control as u8 != 0
+    }
+
+    pub fn just_pressed(&self, control: Control) -> bool {
+        self.pressed(control) && self.previous.bits & control as u8 == 0
+    }
+
+    /// How far thrust is pushed, from `0.0` to `1.0`.
+    pub fn throttle(&self) -> f32 {
+        self.current.throttle()
+    }
+
+    /// How far the ship is steered, from `-1.0` clockwise to `1.0` counterclockwise.
+    pub fn steering(&self) -> f32 {
+        self.current.steering()
+    }
+
+    pub fn advance(&mut self, frame: ControlFrame) {
+        self.previous = self.current;
+        self.current = frame;
+    }
+}
+
+fn source_commands_system(
+    controls: Res<Controls>,
+    time_step: Res<TimeStep>,
+    replay: Option<Res<Replay>>,
+    remote: Res<RemoteCommands>,
+    mut query: Query<(
+        &ControlSource,
+        Option<&Transform>,
+        Option<&SteeringControl>,
+        &mut ShipCommands,
+    )>,
+) {
+    for (source, transform, steering, mut commands) in query.iter_mut() {
+        let frame = match source {
+            ControlSource::Local(player) => {
+                let frame = controls.frame(*player);
+                match (controls.aim(*player), transform, steering) {
+                    (Some(aim), Some(transform), Some(steering)) => {
+                        let heading = (transform.rotation * Vec3::X).truncate();
+                        let direction = aim.direction_from(transform.translation.truncate());
+                        // Turn just enough to face the target by the end of the tick.
+                        let turn = steering.0.get() * time_step.0;
+                        let error = heading.angle_between(direction);
+                        let steering = if error.is_finite() { error / turn } else { 0.0 };
+                        ControlFrame::new(frame.bits, frame.throttle(), steering)
+                    }
+                    _ => frame,
+                }
+            }
+            ControlSource::Replay(stream) => replay
+                .as_ref()
+                .map_or_else(ControlFrame::default, |replay| replay.frame(*stream)),
+            ControlSource::Remote(peer) => remote.frame(*peer),
+            ControlSource::Bot => continue,
+        };
+        commands.advance(frame);
+    }
+}
+
+fn bot_commands_system(
+    mut bots: Query<(
+        Entity,
+        &ControlSource,
+        &Transform,
+        Option<&Velocity>,
+        Option<&Faction>,
+        Option<&Weapon>,
+        &mut ShipCommands,
+    )>,
+    targets: Query<
+        (Entity, &Transform, Option<&Velocity>, Option<&Faction>),
+        (
+            With<Collidable>,
+            Or<(With<Asteroid>, With<Ufo>, With<Ship>)>,
+        ),
+    >,
+) {
+    for (entity, source, transform, velocity, faction, weapon, mut commands) in bots.iter_mut() {
+        if *source != ControlSource::Bot {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let velocity = velocity.map_or(Vec2::ZERO, |velocity| **velocity);
+        let heading = (transform.rotation * Vec3::X).truncate();
+
+        let target = targets
+            .iter()
+            .filter(|(other, .., other_faction)| {
+                *other != entity && (faction.is_none() || *other_faction != faction)
+            })
+            .map(|(_, target, target_velocity, _)| {
+                let offset = target.translation.truncate() - position;
+                (
+                    offset,
+                    target_velocity.map_or(Vec2::ZERO, |velocity| **velocity),
+                )
+            })
+            .min_by(|(a, _), (b, _)| a.length_squared().total_cmp(&b.length_squared()));
+
+        let Some((offset, target_velocity)) = target else {
+            commands.advance(ControlFrame::default());
+            continue;
+        };
+
+        let speed = weapon.map_or(600.0, |weapon| weapon.projectile.speed);
+        let aim = intercept(offset, target_velocity - velocity, speed)
+            .map_or(offset.normalize_or_zero(), |(_, direction)| direction);
+        let error = heading.angle_between(aim);
+
+        let mut bits = 0;
+
+        if error > PI / 36.0 {
+            bits |= Control::Left as u8;
+        } else if error < -PI / 36.0 {
+            bits |= Control::Right as u8;
+        }
+
+        // Let go of the trigger between shots, weapons only fire on a fresh press.
+        if error.abs() < PI / 18.0 && !commands.pressed(Control::Fire) {
+            bits |= Control::Fire as u8;
+        }
+
+        if offset.length() > 250.0 && error.abs() < PI / 6.0 {
+            bits |= Control::Thrust as u8;
+        }
+
+        commands.advance(ControlFrame::from_bits(bits));
+    }
+}
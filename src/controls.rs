@@ -0,0 +1,687 @@
+use std::{io, sync::Arc};
+
+use bevy::{ecs::schedule::ScheduleLabel, input::InputSystem, prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    players::{Player, MAX_PLAYERS},
+    storage::{config_storage, Storage},
+};
+
+const VERSION: u32 = 1;
+
+/// Resolves the [`Action`]s of every player from their bindings, loaded from and saved to the
+/// given storage.
+pub struct ControlsPlugin {
+    storage: Arc<dyn Storage>,
+    dead_zone: f32,
+}
+
+impl ControlsPlugin {
+    pub fn with_storage(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            dead_zone: 0.15,
+        }
+    }
+
+    /// Sets how far gamepad axes have to move from rest before they count at all.
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+}
+
+impl Default for ControlsPlugin {
+    /// Stores the bindings in the platform config directory, or in browser storage on the web.
+    fn default() -> Self {
+        Self::with_storage(config_storage("bindings"))
+    }
+}
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Controls>()
+            .init_resource::<Actions>()
+            .insert_resource(PlayerBindings::load(self.storage.as_ref()))
+            .insert_resource(BindingStorage(self.storage.clone()))
+            .insert_resource(DeadZone(self.dead_zone.clamp(0.0, 0.95)))
+            .add_systems(Startup, touch_overlay_setup_system)
+            .add_systems(PreUpdate, action_system.after(InputSystem))
+            .add_systems(Update, touch_overlay_system);
+    }
+}
+
+/// Systems that fill [`Controls`] for the current fixed tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct ControlsLabel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Control {
+    Thrust = 1 << 0,
+    Left = 1 << 1,
+    Right = 1 << 2,
+    Fire = 1 << 3,
+    Hyperspace = 1 << 4,
+    Shield = 1 << 5,
+}
+
+/// Ship controls of one fixed tick: a set of pressed [`Control`]s along with how far thrust and
+/// steering are pushed, quantized to a byte each so that they can be recorded and replayed as is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ControlFrame {
+    pub bits: u8,
+    /// From `0` to `255`.
+    pub throttle: u8,
+    /// From `-127` clockwise to `127` counterclockwise.
+    pub steering: i8,
+}
+
+impl ControlFrame {
+    pub fn new(bits: u8, throttle: f32, steering: f32) -> Self {
+        Self {
+            bits,
+            throttle: (throttle.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8,
+            steering: (steering.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8,
+        }
+    }
+
+    /// Digital controls, with thrust and steering fully pushed while held.
+    pub fn from_bits(bits: u8) -> Self {
+        let held = |control: Control| bits & control as u8 != 0;
+        let throttle = if held(Control::Thrust) { 1.0 } else { 0.0 };
+        let steering = match (held(Control::Left), held(Control::Right)) {
+            (true, false) => 1.0,
+            (false, true) => -1.0,
+            _ => 0.0,
+        };
+        Self::new(bits, throttle, steering)
+    }
+
+    pub fn from_bytes([bits, throttle, steering]: [u8; 3]) -> Self {
+        Self {
+            bits,
+            throttle,
+            steering: steering as i8,
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; 3] {
+        [self.bits, self.throttle, self.steering as u8]
+    }
+
+    pub fn throttle(&self) -> f32 {
+        self.throttle as f32 / u8::MAX as f32
+    }
+
+    pub fn steering(&self) -> f32 {
+        self.steering as f32 / i8::MAX as f32
+    }
+}
+
+/// Ship controls of the local players sampled once per fixed tick, handed to their ships as
+/// `ShipCommands`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Resource)]
+pub struct Controls {
+    current: [ControlFrame; MAX_PLAYERS],
+    aim: [Option<AimTarget>; MAX_PLAYERS],
+}
+
+impl Controls {
+    pub fn frame(&self, player: Player) -> ControlFrame {
+        self.current[*player]
+    }
+
+    /// Where the player aims, turned into steering once the position of their ship is known.
+    pub fn aim(&self, player: Player) -> Option<AimTarget> {
+        self.aim[*player]
+    }
+
+    /// Moves on to the next tick with the given controls and aim of every player.
+    pub fn advance(
+        &mut self,
+        frames: [ControlFrame; MAX_PLAYERS],
+        aim: [Option<AimTarget>; MAX_PLAYERS],
+    ) {
+        self.current = frames;
+        self.aim = aim;
+    }
+}
+
+/// Something a player can do, whatever the device it comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    /// Positive turns counterclockwise, negative clockwise.
+    Rotate,
+    Thrust,
+    Fire,
+    Hyperspace,
+    Shield,
+    Pause,
+}
+
+impl Action {
+    const COUNT: usize = 6;
+}
+
+/// Physical input an action can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Source {
+    Key(KeyCode),
+    /// Button of the player's gamepad.
+    Button(GamepadButtonType),
+    /// Axis of the player's gamepad.
+    Axis(GamepadAxisType),
+    /// Area of the screen, held while touched.
+    Touch(TouchZone),
+    Mouse(MouseButton),
+}
+
+impl Source {
+    pub fn name(&self) -> String {
+        match self {
+            Source::Key(key) => format!("{key:?}"),
+            Source::Button(button) => format!("Pad {button:?}"),
+            Source::Axis(axis) => format!("Pad {axis:?}"),
+            Source::Touch(_) => "Touch".to_string(),
+            Source::Mouse(button) => format!("Mouse {button:?}"),
+        }
+    }
+}
+
+/// Rectangle of the screen, in fractions of the window size from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TouchZone {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl TouchZone {
+    const fn new(left: f32, top: f32, width: f32, height: f32) -> Self {
+        Self {
+            left,
+            top,
+            width,
+            height,
+        }
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        (self.left..self.left + self.width).contains(&point.x)
+            && (self.top..self.top + self.height).contains(&point.y)
+    }
+}
+
+/// Feeds the value of the source, multiplied by `scale`, into the action. Buttons, keys and
+/// touches read as `1.0` while held.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub source: Source,
+    pub action: Action,
+    pub scale: f32,
+}
+
+impl Binding {
+    fn new(source: Source, action: Action) -> Self {
+        Self {
+            source,
+            action,
+            scale: 1.0,
+        }
+    }
+
+    fn scaled(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// How a player steers their ship.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlScheme {
+    /// Rotate left and right.
+    #[default]
+    Classic,
+    /// Point the ship with the mouse cursor or the right stick, which also fires while pushed.
+    Aim,
+}
+
+impl ControlScheme {
+    pub fn name(self) -> &'static str {
+        match self {
+            ControlScheme::Classic => "CLASSIC",
+            ControlScheme::Aim => "AIM",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            ControlScheme::Classic => ControlScheme::Aim,
+            ControlScheme::Aim => ControlScheme::Classic,
+        }
+    }
+}
+
+/// Everything bound to the actions of one player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    pub bindings: Vec<Binding>,
+    /// Id of the gamepad its button and axis sources are read from.
+    pub gamepad: Option<usize>,
+    #[serde(default)]
+    pub scheme: ControlScheme,
+}
+
+impl Bindings {
+    /// Default keys of every player, the rest of their bindings being shared.
+    const KEYS: [[KeyCode; 6]; MAX_PLAYERS] = [
+        [
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Up,
+            KeyCode::Space,
+            KeyCode::Down,
+            KeyCode::ShiftLeft,
+        ],
+        [
+            KeyCode::A,
+            KeyCode::D,
+            KeyCode::W,
+            KeyCode::E,
+            KeyCode::S,
+            KeyCode::Q,
+        ],
+        [
+            KeyCode::J,
+            KeyCode::L,
+            KeyCode::I,
+            KeyCode::O,
+            KeyCode::K,
+            KeyCode::U,
+        ],
+        [
+            KeyCode::Numpad4,
+            KeyCode::Numpad6,
+            KeyCode::Numpad8,
+            KeyCode::Numpad0,
+            KeyCode::Numpad5,
+            KeyCode::NumpadEnter,
+        ],
+    ];
+
+    /// On-screen buttons of the first player, on touch devices.
+    const TOUCH: [(TouchZone, Action, f32); 7] = [
+        (TouchZone::new(0.0, 0.75, 0.15, 0.25), Action::Rotate, 1.0),
+        (TouchZone::new(0.15, 0.75, 0.15, 0.25), Action::Rotate, -1.0),
+        (TouchZone::new(0.7, 0.75, 0.15, 0.25), Action::Thrust, 1.0),
+        (TouchZone::new(0.85, 0.75, 0.15, 0.25), Action::Fire, 1.0),
+        (
+            TouchZone::new(0.85, 0.5, 0.15, 0.25),
+            Action::Hyperspace,
+            1.0,
+        ),
+        (TouchZone::new(0.7, 0.5, 0.15, 0.25), Action::Shield, 1.0),
+        (TouchZone::new(0.9, 0.0, 0.1, 0.1), Action::Pause, 1.0),
+    ];
+
+    pub fn default_for(player: Player) -> Self {
+        let [left, right, thrust, fire, hyperspace, shield] = Self::KEYS[*player];
+
+        let mut bindings = vec![
+            Binding::new(Source::Key(left), Action::Rotate),
+            Binding::new(Source::Key(right), Action::Rotate).scaled(-1.0),
+            Binding::new(Source::Key(thrust), Action::Thrust),
+            Binding::new(Source::Key(fire), Action::Fire),
+            Binding::new(Source::Key(hyperspace), Action::Hyperspace),
+            Binding::new(Source::Key(shield), Action::Shield),
+            Binding::new(Source::Button(GamepadButtonType::DPadLeft), Action::Rotate),
+            Binding::new(Source::Button(GamepadButtonType::DPadRight), Action::Rotate).scaled(-1.0),
+            Binding::new(Source::Axis(GamepadAxisType::LeftStickX), Action::Rotate).scaled(-1.0),
+            Binding::new(Source::Axis(GamepadAxisType::LeftStickY), Action::Thrust),
+            Binding::new(Source::Button(GamepadButtonType::DPadUp), Action::Thrust),
+            Binding::new(
+                Source::Button(GamepadButtonType::RightTrigger2),
+                Action::Thrust,
+            ),
+            Binding::new(Source::Button(GamepadButtonType::South), Action::Fire),
+            Binding::new(Source::Button(GamepadButtonType::East), Action::Hyperspace),
+            Binding::new(Source::Button(GamepadButtonType::West), Action::Shield),
+            Binding::new(Source::Button(GamepadButtonType::Start), Action::Pause),
+        ];
+
+        if *player == 0 {
+            bindings.push(Binding::new(Source::Key(KeyCode::P), Action::Pause));
+            bindings.push(Binding::new(Source::Key(KeyCode::Escape), Action::Pause));
+            bindings.push(Binding::new(Source::Mouse(MouseButton::Left), Action::Fire));
+            bindings.extend(Self::TOUCH.map(|(zone, action, scale)| {
+                Binding::new(Source::Touch(zone), action).scaled(scale)
+            }));
+        }
+
+        Self {
+            bindings,
+            gamepad: Some(*player),
+            scheme: ControlScheme::default(),
+        }
+    }
+}
+
+/// Input bindings of every player, by player index.
+#[derive(Debug, Clone, PartialEq, Resource, Deref, DerefMut)]
+pub struct PlayerBindings(pub Vec<Bindings>);
+
+impl Default for PlayerBindings {
+    fn default() -> Self {
+        Self(
+            (0..MAX_PLAYERS)
+                .map(Player)
+                .map(Bindings::default_for)
+                .collect(),
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct SavedBindings<'a> {
+    version: u32,
+    players: &'a [Bindings],
+}
+
+/// Players are read one by one so damaged bindings only reset the player they belong to.
+#[derive(Deserialize)]
+struct LoadedBindings {
+    version: u32,
+    players: Vec<ron::Value>,
+}
+
+impl PlayerBindings {
+    fn load(storage: &dyn Storage) -> Self {
+        let contents = match storage.load() {
+            Ok(Some(contents)) => contents,
+            Ok(None) => return Self::default(),
+            Err(error) => {
+                warn!("Failed to read input bindings: {error}");
+                return Self::default();
+            }
+        };
+
+        let loaded = match ron::from_str::<LoadedBindings>(&contents) {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                warn!("Discarding unreadable input bindings: {error}");
+                return Self::default();
+            }
+        };
+
+        if loaded.version != VERSION {
+            warn!(
+                "Discarding input bindings saved with version {}",
+                loaded.version
+            );
+            return Self::default();
+        }
+
+        let mut players = loaded.players.into_iter();
+        Self(
+            (0..MAX_PLAYERS)
+                .map(Player)
+                .map(|player| {
+                    players
+                        .next()
+                        .and_then(|value| value.into_rust::<Bindings>().ok())
+                        .unwrap_or_else(|| Bindings::default_for(player))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn save(&self, storage: &dyn Storage) {
+        let saved = SavedBindings {
+            version: VERSION,
+            players: &self.0,
+        };
+        let result = ron::ser::to_string_pretty(&saved, Default::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+            .and_then(|contents| storage.save(&contents));
+
+        if let Err(error) = result {
+            error!("Failed to save input bindings: {error}");
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct BindingStorage(pub Arc<dyn Storage>);
+
+/// Distance from rest under which gamepad axes read as `0.0`, the rest of their travel being
+/// stretched back to the full range.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Deref)]
+pub struct DeadZone(f32);
+
+impl DeadZone {
+    fn apply(self, value: f32) -> f32 {
+        if value.abs() <= self.0 {
+            0.0
+        } else {
+            value.signum() * (value.abs() - self.0) / (1.0 - self.0)
+        }
+    }
+}
+
+/// Where a player using the [`ControlScheme::Aim`] scheme wants their ship to point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AimTarget {
+    /// Point of the arena under the mouse cursor.
+    Point(Vec2),
+    /// Direction the right stick is pushed in.
+    Direction(Vec2),
+}
+
+impl AimTarget {
+    pub fn direction_from(self, position: Vec2) -> Vec2 {
+        match self {
+            AimTarget::Point(point) => point - position,
+            AimTarget::Direction(direction) => direction,
+        }
+    }
+}
+
+/// Value of every action of every player on the current frame, from `-1.0` to `1.0`.
+#[derive(Debug, Default, Resource)]
+pub struct Actions {
+    current: [[f32; Action::COUNT]; MAX_PLAYERS],
+    previous: [[f32; Action::COUNT]; MAX_PLAYERS],
+    aim: [Option<AimTarget>; MAX_PLAYERS],
+}
+
+impl Actions {
+    /// How far an action has to go before it counts as pressed.
+    const THRESHOLD: f32 = 0.5;
+
+    pub fn value(&self, player: Player, action: Action) -> f32 {
+        self.current[*player][action as usize]
+    }
+
+    pub fn pressed(&self, player: Player, action: Action) -> bool {
+        self.value(player, action).abs() >= Self::THRESHOLD
+    }
+
+    pub fn just_pressed(&self, player: Player, action: Action) -> bool {
+        self.pressed(player, action)
+            && self.previous[*player][action as usize].abs() < Self::THRESHOLD
+    }
+
+    pub fn aim(&self, player: Player) -> Option<AimTarget> {
+        self.aim[*player]
+    }
+
+    /// Whether any player has just pressed the action.
+    pub fn any_just_pressed(&self, action: Action) -> bool {
+        (0..MAX_PLAYERS).any(|player| self.just_pressed(Player(player), action))
+    }
+
+    /// Ship controls of the player for the next fixed tick.
+    pub fn controls(&self, player: Player) -> ControlFrame {
+        let rotate = self.value(player, Action::Rotate);
+        let throttle = self.value(player, Action::Thrust);
+
+        let bits = [
+            (rotate >= Self::THRESHOLD, Control::Left),
+            (rotate <= -Self::THRESHOLD, Control::Right),
+            (self.pressed(player, Action::Thrust), Control::Thrust),
+            (self.pressed(player, Action::Fire), Control::Fire),
+            (
+                self.pressed(player, Action::Hyperspace),
+                Control::Hyperspace,
+            ),
+            (self.pressed(player, Action::Shield), Control::Shield),
+        ]
+        .into_iter()
+        .filter(|(pressed, _)| *pressed)
+        .fold(0, |bits, (_, control)| bits | control as u8);
+
+        ControlFrame::new(bits, throttle, rotate)
+    }
+}
+
+#[derive(Debug, Component)]
+struct TouchButton;
+
+fn action_system(
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    button_axes: Res<Axis<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    dead_zone: Res<DeadZone>,
+    mouse: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    bindings: Res<PlayerBindings>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut actions: ResMut<Actions>,
+) {
+    let window = primary_window.get_single().ok();
+    let size = window.map_or(Vec2::ONE, |window| {
+        Vec2::new(window.width(), window.height())
+    });
+    let cursor = window
+        .and_then(|window| window.cursor_position())
+        .zip(cameras.get_single().ok())
+        .and_then(|(cursor, (camera, transform))| camera.viewport_to_world_2d(transform, cursor));
+    let touched = touches
+        .iter()
+        .map(|touch| touch.position() / size)
+        .collect::<Vec<_>>();
+
+    actions.previous = actions.current;
+
+    for (player, bindings) in bindings.iter().enumerate().take(MAX_PLAYERS) {
+        let gamepad = bindings.gamepad.map(Gamepad::new);
+        let mut values = [0.0; Action::COUNT];
+
+        for binding in bindings.bindings.iter() {
+            let held = |held: bool| if held { 1.0 } else { 0.0 };
+            let value = match binding.source {
+                Source::Key(key) => held(keyboard.pressed(key)),
+                // Triggers report how far they are pulled, other buttons read as held.
+                Source::Button(button) => gamepad
+                    .map(|gamepad| GamepadButton::new(gamepad, button))
+                    .map_or(0.0, |button| {
+                        button_axes.get(button).map_or_else(
+                            || held(buttons.pressed(button)),
+                            |value| dead_zone.apply(value),
+                        )
+                    }),
+                Source::Axis(axis) => gamepad
+                    .and_then(|gamepad| axes.get(GamepadAxis::new(gamepad, axis)))
+                    .map_or(0.0, |value| dead_zone.apply(value)),
+                Source::Touch(zone) => held(touched.iter().any(|point| zone.contains(*point))),
+                Source::Mouse(button) => held(mouse.pressed(button)),
+            };
+            values[binding.action as usize] += value * binding.scale;
+        }
+
+        // The right stick wins over the cursor, which only aims for players bound to the mouse.
+        let stick = gamepad
+            .map(|gamepad| {
+                let axis = |axis| {
+                    axes.get(GamepadAxis::new(gamepad, axis))
+                        .unwrap_or_default()
+                };
+                Vec2::new(
+                    axis(GamepadAxisType::RightStickX),
+                    axis(GamepadAxisType::RightStickY),
+                )
+            })
+            .filter(|stick| stick.length() > **dead_zone)
+            .map(|stick| stick.normalize() * dead_zone.apply(stick.length()));
+        let uses_mouse = bindings
+            .bindings
+            .iter()
+            .any(|binding| matches!(binding.source, Source::Mouse(_)));
+
+        let aim = match bindings.scheme {
+            ControlScheme::Classic => None,
+            ControlScheme::Aim => stick
+                .map(AimTarget::Direction)
+                .or(cursor.filter(|_| uses_mouse).map(AimTarget::Point)),
+        };
+
+        if let Some(aim) = aim {
+            values[Action::Rotate as usize] = 0.0;
+            if let AimTarget::Direction(direction) = aim {
+                values[Action::Fire as usize] += direction.length();
+            }
+        }
+
+        actions.current[player] = values.map(|value| value.clamp(-1.0, 1.0));
+        actions.aim[player] = aim;
+    }
+}
+
+fn touch_overlay_setup_system(mut commands: Commands, bindings: Res<PlayerBindings>) {
+    let zones = bindings
+        .iter()
+        .flat_map(|bindings| bindings.bindings.iter())
+        .filter_map(|binding| match binding.source {
+            Source::Touch(zone) => Some(zone),
+            _ => None,
+        });
+
+    for zone in zones {
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(zone.left * 100.0),
+                    top: Val::Percent(zone.top * 100.0),
+                    width: Val::Percent(zone.width * 100.0),
+                    height: Val::Percent(zone.height * 100.0),
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..Default::default()
+                },
+                border_color: Color::rgba(1.0, 1.0, 1.0, 0.2).into(),
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            TouchButton,
+        ));
+    }
+}
+
+/// Reveals the on-screen buttons once the screen has been touched.
+fn touch_overlay_system(
+    touches: Res<Touches>,
+    mut query: Query<&mut Visibility, With<TouchButton>>,
+) {
+    if touches.iter_just_pressed().next().is_none() {
+        return;
+    }
+
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
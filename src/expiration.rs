@@ -6,7 +6,7 @@ pub struct ExpirationPlugin;
 
 impl Plugin for ExpirationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostUpdate, expiration_system);
+        app.add_systems(FixedUpdate, expiration_system);
     }
 }
 
@@ -20,12 +20,12 @@ impl Expiration {
 }
 
 fn expiration_system(
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     mut commands: Commands,
     mut query: Query<(Entity, &mut Expiration)>,
 ) {
     for (entity, mut expiration) in query.iter_mut() {
-        expiration.0.tick(time.delta());
+        expiration.0.tick(fixed_time.period);
 
         if expiration.0.finished() {
             commands.entity(entity).despawn();
@@ -1,42 +1,178 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
+use bevy_prototype_lyon::{
+    plugin::BuildShapes,
+    prelude::{Fill, Stroke},
+};
 
-pub struct FlickPlugin;
+use crate::{
+    clock::GameClock,
+    tween::{Ease, Keyframes, Repeat, Tween},
+};
+
+#[derive(Default)]
+pub struct FlickPlugin {
+    settings: FlickSettings,
+}
+
+impl FlickPlugin {
+    pub fn with_settings(settings: FlickSettings) -> Self {
+        Self { settings }
+    }
+}
 
 impl Plugin for FlickPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostUpdate, (flick_removed_system, flick_system));
+        app.insert_resource(self.settings.clone()).add_systems(
+            PostUpdate,
+            (flick_removed_system, flick_system).before(BuildShapes),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlickStyle {
+    /// Hard on/off blinking at the requested frequency.
+    Blink,
+    /// Smooth fade between fully and partially opaque.
+    Pulse,
+    /// Smooth blend of the shape colors towards a tint.
+    Tint(Color),
+}
+
+/// Global accessibility setting for every [`Flick`], which can be changed at any time.
+#[derive(Debug, Clone, Resource)]
+pub struct FlickSettings {
+    pub style: FlickStyle,
+    /// Shortest cycle the reduced flicker styles are allowed to use.
+    pub min_period: Duration,
+}
+
+impl Default for FlickSettings {
+    fn default() -> Self {
+        Self {
+            style: FlickStyle::Blink,
+            min_period: Duration::from_millis(500),
+        }
+    }
+}
+
+impl FlickSettings {
+    /// Reads the style from the `BEVYROIDS_FLICKER` variable (`blink`, `pulse` or `tint`).
+    pub fn from_env() -> Self {
+        let style = match std::env::var("BEVYROIDS_FLICKER").as_deref() {
+            Ok("pulse") => FlickStyle::Pulse,
+            Ok("tint") => FlickStyle::Tint(Color::rgb(0.3, 0.7, 1.0)),
+            _ => FlickStyle::Blink,
+        };
+
+        Self {
+            style,
+            ..Default::default()
+        }
+    }
+
+    fn tween(&self, frequency: Duration, origin: &FlickOrigin) -> Tween {
+        let half_period = (frequency * 2).max(self.min_period) / 2;
+
+        match self.style {
+            FlickStyle::Blink => Tween::flick(frequency),
+
+            FlickStyle::Pulse => Tween::new(half_period)
+                .with_repeat(Repeat::PingPong)
+                .with_alpha(Keyframes::from_to(1.0, 0.25, Ease::SineInOut)),
+
+            FlickStyle::Tint(tint) => {
+                let mut tween = Tween::new(half_period).with_repeat(Repeat::PingPong);
+                if let Some(fill) = origin.fill {
+                    tween = tween.with_fill(Keyframes::from_to(fill, tint, Ease::SineInOut));
+                }
+                if let Some(stroke) = origin.stroke {
+                    tween = tween.with_stroke(Keyframes::from_to(stroke, tint, Ease::SineInOut));
+                }
+                tween
+            }
+        }
     }
 }
 
 #[derive(Debug, Component)]
-pub struct Flick(Timer);
+pub struct Flick {
+    frequency: Duration,
+    tween: Option<Tween>,
+}
 
 impl Flick {
     pub fn new(frequency: Duration) -> Self {
-        Self(Timer::new(frequency, TimerMode::Repeating))
+        Self {
+            frequency,
+            tween: None,
+        }
     }
 }
 
-fn flick_system(time: Res<Time>, mut query: Query<(&mut Flick, &mut Visibility)>) {
-    for (mut flick, mut visibility) in query.iter_mut() {
-        flick.0.tick(time.delta());
+/// Colors the shape had before it started flicking, restored once [`Flick`] is removed.
+#[derive(Debug, Component)]
+struct FlickOrigin {
+    fill: Option<Color>,
+    stroke: Option<Color>,
+}
 
-        if flick.0.finished() {
-            *visibility = if *visibility == Visibility::Hidden {
-                Visibility::Inherited
-            } else {
-                Visibility::Hidden
-            }
+fn flick_system(
+    clock: Res<GameClock>,
+    settings: Res<FlickSettings>,
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Flick,
+        Option<&FlickOrigin>,
+        Option<&mut Fill>,
+        Option<&mut Stroke>,
+    )>,
+) {
+    for (entity, mut flick, origin, fill, stroke) in query.iter_mut() {
+        if flick.tween.is_none() || settings.is_changed() {
+            let tween = match origin {
+                Some(origin) => settings.tween(flick.frequency, origin),
+                None => {
+                    let origin = FlickOrigin {
+                        fill: fill.as_ref().map(|fill| fill.color),
+                        stroke: stroke.as_ref().map(|stroke| stroke.color),
+                    };
+                    let tween = settings.tween(flick.frequency, &origin);
+                    commands.entity(entity).insert(origin);
+                    tween
+                }
+            };
+            flick.tween = Some(tween);
+        }
+
+        if let Some(tween) = flick.tween.as_mut() {
+            tween.tick(clock.delta());
+            tween.apply(
+                None,
+                fill.map(|f| f.into_inner()),
+                stroke.map(|s| s.into_inner()),
+            );
         }
     }
 }
 
-fn flick_removed_system(mut removed: RemovedComponents<Flick>, mut query: Query<&mut Visibility>) {
+fn flick_removed_system(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Flick>,
+    mut query: Query<(&FlickOrigin, Option<&mut Fill>, Option<&mut Stroke>)>,
+) {
     for entity in removed.iter() {
-        if let Ok(mut visibility) = query.get_mut(entity) {
-            *visibility = Visibility::Inherited
+        if let Ok((origin, fill, stroke)) = query.get_mut(entity) {
+            if let (Some(mut fill), Some(color)) = (fill, origin.fill) {
+                fill.color = color;
+            }
+            if let (Some(mut stroke), Some(color)) = (stroke, origin.stroke) {
+                stroke.color = color;
+            }
+            commands.entity(entity).remove::<FlickOrigin>();
         }
     }
 }
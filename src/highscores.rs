@@ -0,0 +1,404 @@
+use std::{io, sync::Arc};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    score::Score,
+    state::{GameState, StateScoped},
+    storage::{config_storage, Storage},
+    waves::Wave,
+};
+
+const VERSION: u32 = 1;
+const CAPACITY: usize = 10;
+const INITIALS: usize = 3;
+
+/// Loads the table from its storage on build and writes it back whenever an entry is added.
+pub struct HighScorePlugin {
+    storage: Arc<dyn Storage>,
+}
+
+impl HighScorePlugin {
+    pub fn with_storage(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+}
+
+impl Default for HighScorePlugin {
+    /// Stores the table in the platform config directory, or in browser storage on the web.
+    fn default() -> Self {
+        Self::with_storage(config_storage("highscores"))
+    }
+}
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load(self.storage.as_ref()))
+            .insert_resource(HighScoreStorage(self.storage.clone()))
+            .add_systems(OnEnter(GameState::HighScoreEntry), entry_setup_system)
+            .add_systems(
+                Update,
+                (entry_system, entry_display_system)
+                    .chain()
+                    .run_if(in_state(GameState::HighScoreEntry)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct HighScoreStorage(Arc<dyn Storage>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub initials: String,
+    pub score: u32,
+    /// Wave reached, zero for endless games.
+    pub wave: u32,
+    /// Day the score was set, as `YYYY-MM-DD`.
+    pub date: String,
+}
+
+impl HighScore {
+    fn is_valid(&self) -> bool {
+        self.initials.len() == INITIALS && self.initials.chars().all(|c| c.is_ascii_uppercase())
+    }
+}
+
+#[derive(Serialize)]
+struct SavedTable<'a> {
+    version: u32,
+    entries: &'a [HighScore],
+}
+
+/// Entries are read one by one so a single damaged entry does not cost the whole table.
+#[derive(Deserialize)]
+struct LoadedTable {
+    version: u32,
+    entries: Vec<ron::Value>,
+}
+
+/// Best scores, highest first.
+#[derive(Debug, Default, Resource)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    fn load(storage: &dyn Storage) -> Self {
+        let contents = match storage.load() {
+            Ok(Some(contents)) => contents,
+            Ok(None) => return Self::default(),
+            Err(error) => {
+                warn!("Failed to read high scores: {error}");
+                return Self::default();
+            }
+        };
+
+        let table = match ron::from_str::<LoadedTable>(&contents) {
+            Ok(table) => table,
+            Err(error) => {
+                warn!("Discarding unreadable high scores: {error}");
+                return Self::default();
+            }
+        };
+
+        if table.version != VERSION {
+            warn!(
+                "Discarding high scores saved with version {}",
+                table.version
+            );
+            return Self::default();
+        }
+
+        let mut entries = table
+            .entries
+            .into_iter()
+            .filter_map(|value| value.into_rust::<HighScore>().ok())
+            .filter(HighScore::is_valid)
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries.truncate(CAPACITY);
+
+        Self { entries }
+    }
+
+    fn save(&self, storage: &dyn Storage) {
+        let table = SavedTable {
+            version: VERSION,
+            entries: &self.entries,
+        };
+        let result = ron::ser::to_string_pretty(&table, Default::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+            .and_then(|contents| storage.save(&contents));
+
+        if let Err(error) = result {
+            error!("Failed to save high scores: {error}");
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HighScore> {
+        self.entries.iter()
+    }
+
+    /// Whether the score would make it onto the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < CAPACITY
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Inserts below any equal score, so older entries keep their place.
+    fn insert(&mut self, entry: HighScore) {
+        let index = self
+            .entries
+            .partition_point(|other| other.score >= entry.score);
+        self.entries.insert(index, entry);
+        self.entries.truncate(CAPACITY);
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+struct InitialsEntry {
+    letters: [u8; INITIALS],
+    cursor: usize,
+}
+
+#[derive(Debug, Component)]
+struct InitialsText;
+
+fn entry_setup_system(mut commands: Commands) {
+    commands.insert_resource(InitialsEntry {
+        letters: [b'A'; INITIALS],
+        cursor: 0,
+    });
+
+    let text_style = |font_size, color| TextStyle {
+        font_size,
+        color,
+        ..Default::default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            StateScoped(GameState::HighScoreEntry),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "NEW HIGH SCORE",
+                text_style(48.0, Color::WHITE),
+            ));
+            parent.spawn((
+                TextBundle::from_sections(
+                    (0..INITIALS).map(|_| TextSection::new("", text_style(64.0, Color::WHITE))),
+                ),
+                InitialsText,
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Up/Down to change, Enter to confirm",
+                text_style(20.0, Color::GRAY),
+            ));
+        });
+}
+
+fn entry_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    score: Res<Score>,
+    wave: Res<Wave>,
+    storage: Res<HighScoreStorage>,
+    mut entry: ResMut<InitialsEntry>,
+    mut high_scores: ResMut<HighScores>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let cursor = entry.cursor;
+
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        entry.letters[cursor] = if entry.letters[cursor] == b'Z' {
+            b'A'
+        } else {
+            entry.letters[cursor] + 1
+        };
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        entry.letters[cursor] = if entry.letters[cursor] == b'A' {
+            b'Z'
+        } else {
+            entry.letters[cursor] - 1
+        };
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::Left, KeyCode::Back]) {
+        entry.cursor = cursor.saturating_sub(1);
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Right]) {
+        if cursor + 1 < INITIALS {
+            entry.cursor += 1;
+        } else if keyboard_input.just_pressed(KeyCode::Return) {
+            high_scores.insert(HighScore {
+                initials: String::from_utf8_lossy(&entry.letters).into_owned(),
+                score: score.best(),
+                wave: wave.number(),
+                date: today(),
+            });
+            high_scores.save(storage.0.as_ref());
+            next_state.set(GameState::GameOver);
+        }
+    }
+}
+
+fn entry_display_system(
+    entry: Res<InitialsEntry>,
+    mut query: Query<&mut Text, With<InitialsText>>,
+) {
+    if !entry.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        for (index, section) in text.sections.iter_mut().enumerate() {
+            section.value = format!(" {} ", entry.letters[index] as char);
+            section.style.color = if index == entry.cursor {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+}
+
+fn today() -> String {
+    #[cfg(target_arch = "wasm32")]
+    let seconds = (js_sys::Date::now() / 1000.0) as u64;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    // Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn entry(initials: &str, score: u32) -> HighScore {
+        HighScore {
+            initials: initials.to_string(),
+            score,
+            wave: 1,
+            date: "2024-01-01".to_string(),
+        }
+    }
+
+    fn stored(contents: &str) -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        storage.save(contents).unwrap();
+        storage
+    }
+
+    fn scores(table: &HighScores) -> Vec<(&str, u32)> {
+        table
+            .iter()
+            .map(|entry| (entry.initials.as_str(), entry.score))
+            .collect()
+    }
+
+    #[test]
+    fn inserts_highest_first_and_after_equal_scores() {
+        let mut table = HighScores::default();
+        assert!(!table.qualifies(0));
+        assert!(table.qualifies(1));
+
+        table.insert(entry("AAA", 100));
+        table.insert(entry("BBB", 300));
+        table.insert(entry("CCC", 200));
+        table.insert(entry("DDD", 200));
+
+        assert_eq!(
+            scores(&table),
+            [("BBB", 300), ("CCC", 200), ("DDD", 200), ("AAA", 100)]
+        );
+    }
+
+    #[test]
+    fn keeps_the_best_ten() {
+        let mut table = HighScores::default();
+        for score in 1..=CAPACITY as u32 {
+            table.insert(entry("AAA", score * 10));
+        }
+        assert!(!table.qualifies(10));
+        assert!(table.qualifies(11));
+
+        table.insert(entry("BBB", 15));
+        assert_eq!(table.iter().count(), CAPACITY);
+        assert_eq!(table.iter().last().map(|entry| entry.score), Some(15));
+
+        let storage = MemoryStorage::default();
+        table.save(&storage);
+        assert_eq!(scores(&HighScores::load(&storage)), scores(&table));
+    }
+
+    #[test]
+    fn skips_damaged_entries_on_load() {
+        let storage = stored(
+            r#"(
+                version: 1,
+                entries: [
+                    (initials: "AAA", score: 100, wave: 2, date: "2024-01-01"),
+                    (initials: "BBB", score: "lots"),
+                    (initials: "lowercase", score: 300, wave: 1, date: "2024-01-01"),
+                    (initials: "CCC", score: 200, wave: 3, date: "2024-01-02"),
+                ],
+            )"#,
+        );
+
+        assert_eq!(
+            scores(&HighScores::load(&storage)),
+            [("CCC", 200), ("AAA", 100)]
+        );
+    }
+
+    #[test]
+    fn discards_other_versions() {
+        let storage = stored(
+            r#"(
+                version: 2,
+                entries: [(initials: "AAA", score: 100, wave: 2, date: "2024-01-01")],
+            )"#,
+        );
+
+        assert_eq!(HighScores::load(&storage).iter().count(), 0);
+    }
+}
@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+
+use crate::{
+    players::{Player, PlayerCount, MAX_PLAYERS},
+    score::{Score, ScoreSystemLabel},
+    state::{GameState, StateVisible},
+};
+
+pub struct LivesPlugin {
+    starting: u32,
+    extra_lives: ExtraLives,
+}
+
+impl LivesPlugin {
+    pub fn new(starting: u32, extra_lives: ExtraLives) -> Self {
+        Self {
+            starting,
+            extra_lives,
+        }
+    }
+}
+
+impl Plugin for LivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Lives {
+            count: [self.starting; MAX_PLAYERS],
+            starting: self.starting,
+        })
+        .insert_resource(self.extra_lives.clone())
+        .add_systems(Startup, lives_hud_setup_system)
+        .add_systems(FixedUpdate, extra_life_system.after(ScoreSystemLabel))
+        .add_systems(PostUpdate, lives_hud_system);
+    }
+}
+
+/// Lives left to every player.
+#[derive(Debug, Resource)]
+pub struct Lives {
+    count: [u32; MAX_PLAYERS],
+    starting: u32,
+}
+
+impl Lives {
+    pub fn count(&self, player: Player) -> u32 {
+        self.count[*player]
+    }
+
+    pub fn lose(&mut self, player: Player) {
+        self.count[*player] = self.count[*player].saturating_sub(1);
+    }
+
+    pub fn gain(&mut self, player: Player) {
+        self.count[*player] += 1;
+    }
+
+    pub fn reset(&mut self) {
+        self.count = [self.starting; MAX_PLAYERS];
+    }
+}
+
+/// Score thresholds that award an extra life. Once every threshold has been passed, a new one is
+/// placed every `every` points after the last. Every player works through them on their own.
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ExtraLives {
+    thresholds: Vec<u32>,
+    every: Option<u32>,
+    awarded: [usize; MAX_PLAYERS],
+}
+
+impl ExtraLives {
+    pub fn at(thresholds: impl IntoIterator<Item = u32>) -> Self {
+        let mut thresholds = thresholds.into_iter().collect::<Vec<_>>();
+        thresholds.sort_unstable();
+        Self {
+            thresholds,
+            ..Default::default()
+        }
+    }
+
+    pub fn then_every(mut self, points: u32) -> Self {
+        self.every = Some(points).filter(|points| *points > 0);
+        self
+    }
+
+    pub fn reset(&mut self) {
+        self.awarded = [0; MAX_PLAYERS];
+    }
+
+    fn next(&self, player: Player) -> Option<u32> {
+        let awarded = self.awarded[*player];
+        match self.thresholds.get(awarded) {
+            Some(threshold) => Some(*threshold),
+            None => {
+                let last = self.thresholds.last().copied().unwrap_or(0);
+                let repeats = (awarded - self.thresholds.len() + 1) as u32;
+                self.every.map(|every| last + every * repeats)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+struct LivesText;
+
+fn extra_life_system(
+    score: Res<Score>,
+    mut extra_lives: ResMut<ExtraLives>,
+    mut lives: ResMut<Lives>,
+) {
+    if !score.is_changed() {
+        return;
+    }
+
+    for player in (0..MAX_PLAYERS).map(Player) {
+        while let Some(threshold) = extra_lives.next(player) {
+            if score.get(player) < threshold {
+                break;
+            }
+            extra_lives.awarded[*player] += 1;
+            lives.gain(player);
+        }
+    }
+}
+
+fn lives_hud_setup_system(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_sections((0..MAX_PLAYERS).map(|player| {
+            TextSection::new(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: Player(player).color(),
+                    ..Default::default()
+                },
+            )
+        }))
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(46.0),
+            left: Val::Px(16.0),
+            ..Default::default()
+        }),
+        LivesText,
+        StateVisible(GameState::Playing),
+    ));
+}
+
+fn lives_hud_system(
+    lives: Res<Lives>,
+    count: Res<PlayerCount>,
+    mut query: Query<&mut Text, With<LivesText>>,
+) {
+    if !lives.is_changed() && !count.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        for (player, section) in text.sections.iter_mut().enumerate() {
+            let player = Player(player);
+            section.value = match **count {
+                1 if *player == 0 => format!("LIVES {}", lives.count(player)),
+                count if *player < count => {
+                    format!("{} LIVES {}  ", player.label(), lives.count(player))
+                }
+                _ => String::new(),
+            };
+        }
+    }
+}
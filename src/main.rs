@@ -1,11 +1,13 @@
-#![allow(clippy::type_complexity)]
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
 
 use std::{f32::consts::PI, ops::Range, time::Duration};
 
+use ai::{Action, AiPlugin, AiSystemLabel, Brain, Consideration, Task, Waypoint};
+use aiming::{Aim, Shot, Target};
 use bevy::{
     ecs::{event::Event, schedule::ScheduleLabel},
     prelude::*,
-    time::common_conditions::on_timer,
+    time::common_conditions::on_fixed_timer,
     utils::HashSet,
     window::PrimaryWindow,
 };
@@ -18,19 +20,57 @@ use bevy_prototype_lyon::{
     shapes::Polygon,
 };
 use boundary::{BoundaryPlugin, BoundaryRemoval, BoundaryWrap, Bounding};
-use collision::{Collidable, CollisionPlugin, CollisionSystemLabel, HitEvent};
+use clock::{ClockPlugin, GameClock};
+use collision::{Collidable, CollisionPlugin, CollisionSystemLabel, Faction, HitEvent, Owner};
+use control_source::{ControlSource, ControlSourceLabel, ControlSourcePlugin, ShipCommands};
+use controls::{Actions, Control, ControlFrame, Controls, ControlsLabel, ControlsPlugin};
 use expiration::{Expiration, ExpirationPlugin};
-use flickering::{Flick, FlickPlugin};
+use flickering::{Flick, FlickPlugin, FlickSettings};
+use highscores::{HighScorePlugin, HighScores};
+use lives::{ExtraLives, Lives, LivesPlugin};
 use physics::{AngularVelocity, Damping, PhysicsPlugin, PhysicsSystemLabel, SpeedLimit, Velocity};
+use players::{CpuPlayers, Player, PlayerCount, PlayersPlugin, MAX_PLAYERS};
+use powerups::{
+    DropSource, Piercing, PowerUp, PowerUpDropEvent, PowerUpKind, PowerUpPlugin, PowerUpSettings,
+    PowerUpSystemLabel, PowerUps,
+};
 use rand::{prelude::SliceRandom, Rng};
-use random::{Random, RandomPlugin};
-
+use random::{RandomPlugin, RandomStream, StreamLabel};
+use rebinding::RebindingPlugin;
+use replay::{Replay, ReplayPlugin, ReplaySystemLabel};
+use score::{Score, ScoreEvent, ScorePlugin, ScoreSystemLabel};
+use shield::{Shield, ShieldPlugin, ShieldSystemLabel};
+use state::{GameState, GameplaySystemLabel, StatePlugin, StateScoped};
+use steering::{Behavior, Obstacle, Steering, SteeringPlugin, SteeringSystemLabel};
+use tween::{Ease, Keyframes, Tween, TweenPlugin};
+use versus::{KillEvent, Standings, VersusPlugin, VersusSettings, VersusSystemLabel};
+use waves::{GameMode, Wave, WavePlugin, WaveSettings, WaveSystemLabel};
+
+mod ai;
+mod aiming;
 mod boundary;
+mod clock;
 mod collision;
+mod control_source;
+mod controls;
 mod expiration;
 mod flickering;
+mod highscores;
+mod lives;
 mod physics;
+mod players;
+mod powerups;
 mod random;
+mod rebinding;
+mod replay;
+mod score;
+mod shield;
+mod state;
+mod steering;
+mod storage;
+mod tween;
+mod versus;
+mod waves;
 
 fn main() {
     App::new()
@@ -50,52 +90,196 @@ fn main() {
             medium: 30.0..40.0,
             small: 10.0..20.0,
         })
+        .insert_resource(PointValues {
+            big_asteroid: 20,
+            medium_asteroid: 50,
+            small_asteroid: 100,
+        })
+        .init_resource::<UfoDefinitions>()
+        .init_resource::<HyperspaceSettings>()
         .add_event::<AsteroidSpawnEvent>()
-        .add_plugins(RandomPlugin)
+        .add_plugins(ClockPlugin)
+        .add_plugins(ReplayPlugin::from_env())
+        .add_plugins(RandomPlugin::from_env())
+        .add_plugins(PlayersPlugin::new(1))
+        .add_plugins(ControlsPlugin::default().with_dead_zone(0.2))
         .add_plugins(PhysicsPlugin::with_fixed_time_step(1.0 / 120.0))
         .add_plugins(CollisionPlugin::<Bullet, Asteroid>::new())
-        .add_plugins(CollisionPlugin::<Bullet, Ufo>::new())
-        .add_plugins(CollisionPlugin::<Bullet, Ship>::new())
+        .add_plugins(CollisionPlugin::<Bullet, Ufo>::new().without_friendly_fire())
+        .add_plugins(CollisionPlugin::<Bullet, Ship>::new().without_friendly_fire())
         .add_plugins(CollisionPlugin::<Asteroid, Ship>::new())
         .add_plugins(CollisionPlugin::<Asteroid, Ufo>::new())
         .add_plugins(CollisionPlugin::<Ufo, Ship>::new())
+        .add_plugins(CollisionPlugin::<PowerUp, Ship>::new())
         .add_plugins(BoundaryPlugin)
         .add_plugins(ExpirationPlugin)
-        .add_plugins(FlickPlugin)
+        .add_plugins(FlickPlugin::with_settings(FlickSettings::from_env()))
+        .add_plugins(TweenPlugin)
+        .add_plugins(ScorePlugin)
+        .add_plugins(LivesPlugin::new(
+            3,
+            ExtraLives::at([10_000]).then_every(10_000),
+        ))
+        .add_plugins(WavePlugin::new(
+            GameMode::from_env(),
+            WaveSettings::default(),
+        ))
+        .add_plugins(StatePlugin)
+        .add_plugins(ShieldPlugin)
+        .add_plugins(PowerUpPlugin)
+        .add_plugins(SteeringPlugin)
+        .add_plugins(AiPlugin)
+        .add_plugins(VersusPlugin::new(VersusSettings::default()))
+        .add_plugins(HighScorePlugin::default())
+        .add_plugins(RebindingPlugin)
+        .add_plugins(ControlSourcePlugin)
+        .configure_set(FixedUpdate, WaveSystemLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, ShieldSystemLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, PowerUpSystemLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, SteeringSystemLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, AiSystemLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, VersusSystemLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, ControlsLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, ControlSourceLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, ReplaySystemLabel.in_set(GameplaySystemLabel))
+        .configure_set(FixedUpdate, CollisionSystemLabel.after(PhysicsSystemLabel))
         .add_systems(Startup, setup_system)
+        .add_systems(OnEnter(GameState::MainMenu), main_menu_setup_system)
+        .add_systems(OnEnter(GameState::Paused), pause_setup_system)
+        .add_systems(OnEnter(GameState::GameOver), game_over_setup_system)
+        .add_systems(
+            OnTransition {
+                from: GameState::MainMenu,
+                to: GameState::Playing,
+            },
+            new_game_system,
+        )
+        .add_systems(
+            OnTransition {
+                from: GameState::GameOver,
+                to: GameState::Playing,
+            },
+            new_game_system,
+        )
+        .add_systems(
+            OnTransition {
+                from: GameState::RoundResults,
+                to: GameState::Playing,
+            },
+            new_game_system,
+        )
+        .add_systems(
+            Update,
+            main_menu_system.run_if(in_state(GameState::MainMenu)),
+        )
         .add_systems(
             Update,
+            game_over_system.run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(Update, (pause_system, clock_control_system))
+        .add_systems(
+            FixedUpdate,
+            input_controls_system
+                .run_if(not(resource_exists::<Replay>()))
+                .in_set(ControlsLabel),
+        )
+        .add_systems(
+            FixedUpdate,
             (
                 steering_control_system,
                 thrust_control_system,
                 weapon_control_system,
+                hyperspace_control_system,
+                shield_control_system,
             )
                 .in_set(InputLabel)
+                .in_set(GameplaySystemLabel)
+                .after(ControlSourceLabel)
                 .before(PhysicsSystemLabel),
         )
-        .add_systems(Update, weapon_system.after(InputLabel))
-        .add_systems(Update, thrust_system.after(InputLabel))
-        .add_systems(
-            Update,
-            asteroid_spawn_system.run_if(on_timer(Duration::from_secs_f32(0.5))),
-        )
-        .add_systems(Update, asteroid_generation_system)
         .add_systems(
-            Update,
-            ufo_spawn_system.run_if(on_timer(Duration::from_secs_f32(1.0))),
+            FixedUpdate,
+            (
+                weapon_system,
+                thrust_system.before(PhysicsSystemLabel),
+                asteroid_spawn_system
+                    .run_if(
+                        resource_equals(GameMode::Endless)
+                            .or_else(resource_equals(GameMode::Versus)),
+                    )
+                    .run_if(on_fixed_timer(Duration::from_secs_f32(0.5))),
+                asteroid_generation_system
+                    .after(asteroid_spawn_system)
+                    .after(asteroid_hit_system)
+                    .after(WaveSystemLabel),
+                ufo_spawn_system
+                    .run_if(not(resource_equals(GameMode::Versus)))
+                    .run_if(on_fixed_timer(Duration::from_secs_f32(1.0))),
+                ship_state_system.before(CollisionSystemLabel),
+                (asteroid_hit_system, ship_hit_system, ufo_hit_system)
+                    .after(CollisionSystemLabel)
+                    .before(PowerUpSystemLabel)
+                    .before(VersusSystemLabel)
+                    .before(ScoreSystemLabel),
+            )
+                .after(InputLabel)
+                .in_set(GameplaySystemLabel),
         )
-        .add_systems(Update, explosion_system)
-        .add_systems(Update, ship_state_system.before(CollisionSystemLabel))
-        .add_systems(Update, ufo_state_system.before(CollisionSystemLabel))
-        .add_systems(Update, asteroid_hit_system.after(CollisionSystemLabel))
-        .add_systems(Update, ship_hit_system.after(CollisionSystemLabel))
-        .add_systems(Update, ufo_hit_system.after(CollisionSystemLabel))
+        .add_systems(Update, thrust_flame_system.in_set(GameplaySystemLabel))
         .run();
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
 pub struct InputLabel;
 
+struct UfoSpawn;
+
+impl StreamLabel for UfoSpawn {
+    const LABEL: &'static str = "ufo_spawn";
+}
+
+struct AsteroidSpawn;
+
+impl StreamLabel for AsteroidSpawn {
+    const LABEL: &'static str = "asteroid_spawn";
+}
+
+struct AsteroidShape;
+
+impl StreamLabel for AsteroidShape {
+    const LABEL: &'static str = "asteroid_shape";
+}
+
+struct ShipExplosion;
+
+impl StreamLabel for ShipExplosion {
+    const LABEL: &'static str = "ship_explosion";
+}
+
+struct HyperspaceJump;
+
+impl StreamLabel for HyperspaceJump {
+    const LABEL: &'static str = "hyperspace_jump";
+}
+
+struct AsteroidExplosion;
+
+impl StreamLabel for AsteroidExplosion {
+    const LABEL: &'static str = "asteroid_explosion";
+}
+
+struct UfoExplosion;
+
+impl StreamLabel for UfoExplosion {
+    const LABEL: &'static str = "ufo_explosion";
+}
+
+struct WeaponAim;
+
+impl StreamLabel for WeaponAim {
+    const LABEL: &'static str = "weapon_aim";
+}
+
 #[derive(Debug, Clone, Resource)]
 struct AsteroidSizes {
     big: Range<f32>,
@@ -103,10 +287,64 @@ struct AsteroidSizes {
     small: Range<f32>,
 }
 
+impl AsteroidSizes {
+    fn classify(&self, bounds: &Bounding) -> AsteroidSize {
+        if self.big.contains(bounds) {
+            AsteroidSize::Big
+        } else if self.medium.contains(bounds) {
+            AsteroidSize::Medium
+        } else {
+            AsteroidSize::Small
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AsteroidSize {
+    Big,
+    Medium,
+    Small,
+}
+
+#[derive(Debug, Clone, Resource)]
+struct PointValues {
+    big_asteroid: u32,
+    medium_asteroid: u32,
+    small_asteroid: u32,
+}
+
+impl PointValues {
+    fn asteroid(&self, size: AsteroidSize) -> u32 {
+        match size {
+            AsteroidSize::Big => self.big_asteroid,
+            AsteroidSize::Medium => self.medium_asteroid,
+            AsteroidSize::Small => self.small_asteroid,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Resource)]
+struct HyperspaceSettings {
+    /// Time spent out of the arena between vanishing and reappearing.
+    delay: Duration,
+    /// Chance of the ship blowing up when it comes back.
+    self_destruct_chance: f64,
+}
+
+impl Default for HyperspaceSettings {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(800),
+            self_destruct_chance: 0.1,
+        }
+    }
+}
+
 #[derive(Debug, Component, Default)]
 struct ThrustEngine {
     force: f32,
-    on: bool,
+    /// Share of the force applied, from `0.0` to `1.0`.
+    throttle: f32,
 }
 
 impl ThrustEngine {
@@ -118,13 +356,59 @@ impl ThrustEngine {
     }
 }
 
+/// Turning rate of a ship steered all the way.
 #[derive(Debug, Component, Default)]
 struct SteeringControl(Angle);
 
+/// What happens to a projectile that reaches the edge of the arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeBehavior {
+    Wrap,
+    Remove,
+}
+
+/// How the projectiles of a [`Weapon`] look and travel.
+#[derive(Debug, Clone)]
+struct ProjectileProfile {
+    speed: f32,
+    radius: f32,
+    edge: EdgeBehavior,
+    lifetime: Option<Duration>,
+    /// Adds the velocity of the shooter to the projectile's own.
+    inherit_velocity: bool,
+}
+
+impl Default for ProjectileProfile {
+    fn default() -> Self {
+        Self {
+            speed: 1000.0,
+            radius: 2.0,
+            edge: EdgeBehavior::Remove,
+            lifetime: None,
+            inherit_velocity: false,
+        }
+    }
+}
+
+impl ProjectileProfile {
+    /// Slower shots that wrap around the arena and die out after a fixed range, as in the arcade
+    /// original.
+    fn classic() -> Self {
+        Self {
+            speed: 600.0,
+            edge: EdgeBehavior::Wrap,
+            lifetime: Some(Duration::from_millis(900)),
+            inherit_velocity: true,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug, Component)]
 struct Weapon {
+    rate_of_fire: Duration,
     cooldown: Timer,
-    force: f32,
+    projectile: ProjectileProfile,
     triggered: bool,
     automatic: bool,
 }
@@ -132,8 +416,9 @@ struct Weapon {
 impl Default for Weapon {
     fn default() -> Self {
         Self {
+            rate_of_fire: Duration::ZERO,
             cooldown: Timer::default(),
-            force: 1000.0,
+            projectile: ProjectileProfile::default(),
             triggered: false,
             automatic: false,
         }
@@ -143,10 +428,16 @@ impl Default for Weapon {
 impl Weapon {
     fn new(rate_of_fire: Duration) -> Self {
         Self {
+            rate_of_fire,
             cooldown: Timer::new(rate_of_fire, TimerMode::Repeating),
             ..Default::default()
         }
     }
+
+    fn with_projectile(mut self, projectile: ProjectileProfile) -> Self {
+        self.projectile = projectile;
+        self
+    }
 }
 
 #[derive(Debug, Component, Deref)]
@@ -175,6 +466,12 @@ impl Ship {
             state: ShipState::Spawning(Timer::new(duration, TimerMode::Once)),
         }
     }
+
+    fn hyperspace(duration: Duration) -> Self {
+        Ship {
+            state: ShipState::Hyperspace(Timer::new(duration, TimerMode::Once)),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -183,50 +480,182 @@ enum ShipState {
     Alive,
     Dead(Timer),
     Spawning(Timer),
+    Hyperspace(Timer),
 }
 
 #[derive(Debug, Component, Default)]
 struct Bullet;
 
-#[derive(Debug, Component, Default)]
-struct Ufo {
-    state: UfoState,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum UfoKind {
+    /// Big, slow saucer that fires in random directions.
+    #[default]
+    Large,
+    /// Small saucer that aims at the ship, more precisely as the score rises.
+    Small,
 }
 
-#[derive(Debug)]
-enum UfoState {
-    Alive(Timer),
-    ChangingDirection(Timer),
+/// How a UFO aims its shots.
+#[derive(Debug, Clone)]
+enum UfoAccuracy {
+    Random,
+    /// Maximum aim error in radians, from the first tier whose score has been reached, walking
+    /// the list from the highest score down.
+    Tiers(Vec<(u32, f32)>),
 }
 
-impl Default for UfoState {
-    fn default() -> Self {
-        UfoState::Alive(Timer::new(Duration::from_secs(5), TimerMode::Once))
+impl UfoAccuracy {
+    fn aim(&self, score: u32) -> Aim {
+        match self {
+            UfoAccuracy::Random => Aim::random(),
+            UfoAccuracy::Tiers(tiers) => {
+                let error = tiers
+                    .iter()
+                    .rev()
+                    .find(|(threshold, _)| score >= *threshold)
+                    .map_or(PI, |(_, error)| *error);
+                Aim::lead().with_inaccuracy(error)
+            }
+        }
     }
 }
 
-impl Ufo {
-    fn alive(duration: Duration) -> Self {
-        Ufo {
-            state: UfoState::Alive(Timer::new(duration, TimerMode::Once)),
-        }
+#[derive(Debug, Clone)]
+struct UfoDefinition {
+    kind: UfoKind,
+    /// Hull width, the rest of the shape scales with it.
+    size: f32,
+    points: u32,
+    /// Chance of spawning on every spawn check. A single UFO spawns at most per check, so the
+    /// chances of all definitions add up to no more than `1.0`.
+    spawn_chance: f64,
+    /// Speed across the arena.
+    speed: Range<f32>,
+    /// Movement pattern added on top of crossing the arena.
+    behaviors: Vec<(Behavior, f32)>,
+    /// Decisions on when to attack, dodge and leave.
+    brain: Vec<Action>,
+    rate_of_fire: Range<u64>,
+    projectile_speed: Range<f32>,
+    accuracy: UfoAccuracy,
+}
+
+#[derive(Debug, Clone, Resource)]
+struct UfoDefinitions(Vec<UfoDefinition>);
+
+impl Default for UfoDefinitions {
+    fn default() -> Self {
+        Self(vec![
+            UfoDefinition {
+                kind: UfoKind::Large,
+                size: 30.0,
+                points: 200,
+                spawn_chance: 1.0 / 10.0,
+                speed: 100.0..150.0,
+                behaviors: vec![
+                    (
+                        Behavior::Sine {
+                            axis: Vec2::Y,
+                            amplitude: 80.0,
+                            frequency: 0.25,
+                        },
+                        1.0,
+                    ),
+                    (
+                        Behavior::Wander {
+                            distance: 60.0,
+                            radius: 30.0,
+                            jitter: 4.0,
+                        },
+                        0.3,
+                    ),
+                    (
+                        Behavior::Avoid {
+                            look_ahead: 1.0,
+                            margin: 10.0,
+                        },
+                        3.0,
+                    ),
+                ],
+                brain: vec![
+                    Action::new(Task::Attack, 0.5).when(Consideration::HostileWithin(2000.0)),
+                    Action::new(Task::Evade, 1.0).when(Consideration::ThreatWithin(120.0)),
+                    Action::new(Task::FleeOffScreen, 2.0).when(Consideration::OlderThan(15.0)),
+                    Action::new(Task::Wait, 1.0).when(Consideration::Fixed(0.1)),
+                ],
+                rate_of_fire: 1000..2000,
+                projectile_speed: 300.0..400.0,
+                accuracy: UfoAccuracy::Random,
+            },
+            UfoDefinition {
+                kind: UfoKind::Small,
+                size: 18.0,
+                points: 1000,
+                spawn_chance: 1.0 / 30.0,
+                speed: 150.0..200.0,
+                behaviors: vec![
+                    (
+                        Behavior::ZigZag {
+                            axis: Vec2::Y,
+                            amplitude: 120.0,
+                            frequency: 0.5,
+                        },
+                        1.0,
+                    ),
+                    (
+                        Behavior::Avoid {
+                            look_ahead: 0.8,
+                            margin: 8.0,
+                        },
+                        3.0,
+                    ),
+                ],
+                brain: vec![
+                    Action::new(Task::Attack, 0.6).when(Consideration::HostileWithin(2000.0)),
+                    Action::new(Task::MoveTo(Waypoint::Random), 0.4)
+                        .when(Consideration::NoHostileWithin(2000.0)),
+                    Action::new(Task::Evade, 1.0).when(Consideration::ThreatWithin(100.0)),
+                    Action::new(Task::FleeOffScreen, 2.0).when(Consideration::OlderThan(20.0)),
+                ],
+                rate_of_fire: 800..1500,
+                projectile_speed: 400.0..500.0,
+                accuracy: UfoAccuracy::Tiers(vec![
+                    (0, 20f32.to_radians()),
+                    (10_000, 10f32.to_radians()),
+                    (40_000, 3f32.to_radians()),
+                ]),
+            },
+        ])
     }
+}
 
-    fn changing_direction(duration: Duration) -> Self {
-        Ufo {
-            state: UfoState::ChangingDirection(Timer::new(duration, TimerMode::Once)),
-        }
+impl UfoDefinitions {
+    fn get(&self, kind: UfoKind) -> Option<&UfoDefinition> {
+        self.0.iter().find(|definition| definition.kind == kind)
     }
 }
 
+#[derive(Debug, Component, Default)]
+struct Ufo {
+    kind: UfoKind,
+}
+
 #[derive(Debug, Component, Default)]
 struct Explosion;
 
+#[derive(Debug, Component)]
+struct ModeText;
+
+#[derive(Debug, Component)]
+struct PlayersText;
+
 #[derive(Debug, Component, Default)]
 struct Asteroid;
 
+/// Asteroid to spawn, along with the piercing bullet that split it from its parent, if any, so
+/// that the bullet goes through the fragments instead of destroying them in turn.
 #[derive(Debug, Event)]
-struct AsteroidSpawnEvent(Vec2, Bounding);
+struct AsteroidSpawnEvent(Vec2, Bounding, Option<Entity>);
 
 #[derive(Bundle)]
 struct ExplosionBundle {
@@ -236,6 +665,8 @@ struct ExplosionBundle {
     velocity: Velocity,
     damping: Damping,
     expiration: Expiration,
+    tween: Tween,
+    scope: StateScoped,
 }
 
 impl Default for ExplosionBundle {
@@ -253,75 +684,199 @@ impl Default for ExplosionBundle {
             velocity: Velocity::default(),
             damping: Damping::from(0.97),
             expiration: Expiration::new(Duration::from_secs(1)),
+            tween: Tween::growth(0.06, Duration::from_secs(2)),
+            scope: StateScoped(GameState::Playing),
         }
     }
 }
 
 fn setup_system(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
-    commands.spawn(Ship::spawn(Duration::from_secs(0)));
 }
 
 fn thrust_system(mut query: Query<(&mut Velocity, &ThrustEngine, &Transform)>) {
     for (mut velocity, thrust, transform) in query.iter_mut() {
-        if thrust.on {
+        if thrust.throttle > 0.0 {
             let dir = transform.rotation * Vec3::X;
-            velocity.x += dir.x * thrust.force;
-            velocity.y += dir.y * thrust.force;
+            velocity.x += dir.x * thrust.force * thrust.throttle;
+            velocity.y += dir.y * thrust.force * thrust.throttle;
         }
     }
 }
 
+/// Outline of a ship, with a flame out the back as long as the throttle is open.
+fn ship_path(throttle: f32) -> Path {
+    let mut path_builder = PathBuilder::new();
+    path_builder.move_to(Vec2::ZERO);
+    path_builder.line_to(Vec2::new(-8.0, -8.0));
+    path_builder.line_to(Vec2::new(0.0, 12.0));
+    path_builder.line_to(Vec2::new(8.0, -8.0));
+    path_builder.line_to(Vec2::ZERO);
+    if throttle > 0.0 {
+        path_builder.move_to(Vec2::new(-4.0, -4.0));
+        path_builder.line_to(Vec2::new(0.0, -4.0 - 12.0 * throttle));
+        path_builder.line_to(Vec2::new(4.0, -4.0));
+    }
+    let mut line = path_builder.build();
+    line.0 = line.0.transformed(&Rotation::new(Angle::degrees(-90.0)));
+    line
+}
+
+fn thrust_flame_system(
+    mut query: Query<(&ThrustEngine, &mut Path), (With<Ship>, Changed<ThrustEngine>)>,
+) {
+    for (thrust, mut path) in query.iter_mut() {
+        *path = ship_path(thrust.throttle);
+    }
+}
+
 fn weapon_system(
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
+    power_up_settings: Res<PowerUpSettings>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut rng: Local<RandomStream<WeaponAim>>,
     mut commands: Commands,
-    mut query: Query<(&Bounding, Option<&WeaponTarget>, &Transform, &mut Weapon)>,
-    targets: Query<&Transform>,
+    mut query: Query<(
+        Entity,
+        &Bounding,
+        Option<&Faction>,
+        Option<&WeaponTarget>,
+        Option<&Aim>,
+        Option<&PowerUps>,
+        Option<&Velocity>,
+        &Transform,
+        &mut Weapon,
+    )>,
+    targets: Query<(&Transform, Option<&Velocity>)>,
 ) {
-    for (bounds, target, transform, mut weapon) in query.iter_mut() {
-        weapon.cooldown.tick(time.delta());
+    let arena = primary_window.get_single().map_or(Vec2::ZERO, |window| {
+        Vec2::new(window.width(), window.height())
+    });
+
+    for (shooter, bounds, faction, target, aim, power_ups, shooter_vel, transform, mut weapon) in
+        query.iter_mut()
+    {
+        weapon.cooldown.tick(fixed_time.period);
 
         if weapon.cooldown.finished() && weapon.triggered {
             weapon.triggered = false;
 
-            let bullet_dir = match target.and_then(|target| targets.get(**target).ok()) {
-                Some(target) => (target.translation - transform.translation).normalize_or_zero(),
-                None => transform.rotation * Vec3::X,
+            let shot = Shot {
+                origin: transform.translation.truncate(),
+                speed: weapon.projectile.speed,
+                inherited_velocity: match shooter_vel {
+                    Some(velocity) if weapon.projectile.inherit_velocity => **velocity,
+                    _ => Vec2::ZERO,
+                },
+            };
+            let target =
+                target
+                    .and_then(|target| targets.get(**target).ok())
+                    .map(|(target, velocity)| Target {
+                        position: target.translation.truncate(),
+                        velocity: velocity.map_or(Vec2::ZERO, |velocity| **velocity),
+                    });
+            let bullet_dir = aim
+                .copied()
+                .unwrap_or_default()
+                .with_wrap(weapon.projectile.edge == EdgeBehavior::Wrap)
+                .direction(&mut **rng, shot, target, arena)
+                .map_or(transform.rotation * Vec3::X, |direction| {
+                    direction.extend(0.0)
+                });
+
+            let has = |kind| power_ups.is_some_and(|power_ups| power_ups.has(kind));
+            let spread = power_up_settings.triple_shot_spread;
+            let angles = if has(PowerUpKind::TripleShot) {
+                vec![-spread, 0.0, spread]
+            } else {
+                vec![0.0]
             };
 
-            let bullet_vel = bullet_dir * weapon.force;
-            let bounds = **bounds + 10.0;
-            let bullet_pos = transform.translation + (bullet_dir * bounds);
+            for angle in angles {
+                let bullet_dir = Quat::from_rotation_z(angle) * bullet_dir;
+                let projectile = &weapon.projectile;
+                let mut bullet_vel = bullet_dir.truncate() * projectile.speed;
+                if let Some(shooter_vel) = shooter_vel.filter(|_| projectile.inherit_velocity) {
+                    bullet_vel += **shooter_vel;
+                }
+                let bounds = **bounds + 10.0;
+                let bullet_pos = transform.translation + (bullet_dir * bounds);
+
+                let bullet = commands
+                    .spawn(ShapeBundle {
+                        path: GeometryBuilder::build_as(&shapes::Circle {
+                            radius: projectile.radius,
+                            center: Vec2::ZERO,
+                        }),
+                        ..Default::default()
+                    })
+                    .insert(Fill::color(Color::WHITE))
+                    .insert(Transform::from_translation(Vec3::new(
+                        bullet_pos.x,
+                        bullet_pos.y,
+                        0.0,
+                    )))
+                    .insert(Bullet)
+                    .insert(Owner(shooter))
+                    .insert(StateScoped(GameState::Playing))
+                    .insert(Collidable)
+                    .insert(Bounding::from_radius(projectile.radius))
+                    .insert(Velocity::from(bullet_vel))
+                    .id();
+
+                match projectile.edge {
+                    EdgeBehavior::Wrap => commands.entity(bullet).insert(BoundaryWrap),
+                    EdgeBehavior::Remove => commands.entity(bullet).insert(BoundaryRemoval),
+                };
+
+                if let Some(faction) = faction {
+                    commands.entity(bullet).insert(*faction);
+                }
 
-            commands
-                .spawn(ShapeBundle {
-                    path: GeometryBuilder::build_as(&shapes::Circle {
-                        radius: 2.0,
-                        center: Vec2::ZERO,
-                    }),
-                    ..Default::default()
-                })
-                .insert(Fill::color(Color::WHITE))
-                .insert(Transform::from_translation(Vec3::new(
-                    bullet_pos.x,
-                    bullet_pos.y,
-                    0.0,
-                )))
-                .insert(Bullet)
-                .insert(Collidable)
-                .insert(Bounding::from_radius(2.0))
-                .insert(Velocity::from(Vec2::new(bullet_vel.x, bullet_vel.y)))
-                .insert(BoundaryRemoval);
+                if let Some(lifetime) = projectile.lifetime {
+                    commands.entity(bullet).insert(Expiration::new(lifetime));
+                }
+
+                if has(PowerUpKind::Piercing) {
+                    commands.entity(bullet).insert(Piercing::default());
+                }
+            }
         }
     }
 }
 
 fn ship_state_system(
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    hyperspace: Res<HyperspaceSettings>,
+    mode: Res<GameMode>,
+    versus: Res<VersusSettings>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut rng: Local<RandomStream<HyperspaceJump>>,
+    mut lives: ResMut<Lives>,
+    mut kills: EventWriter<KillEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
     mut commands: Commands,
-    mut ships: Query<(Entity, &mut Ship)>,
+    player_count: Res<PlayerCount>,
+    mut ships: Query<(
+        Entity,
+        &Player,
+        &mut Ship,
+        Option<&mut Transform>,
+        Option<&mut Shield>,
+    )>,
 ) {
-    for (entity, mut ship) in ships.iter_mut() {
+    let mut remaining = ships.iter().len();
+    let positions = ships
+        .iter()
+        .filter_map(|(entity, _, _, transform, _)| {
+            transform.map(|transform| (entity, transform.translation.truncate()))
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, player, mut ship, transform, shield) in ships.iter_mut() {
         match ship.state {
             ShipState::Alive => {}
 
@@ -333,191 +888,274 @@ fn ship_state_system(
                         .remove::<SteeringControl>()
                         .remove::<Weapon>()
                         .remove::<ThrustEngine>()
+                        .remove::<Shield>()
+                        .remove::<PowerUps>()
                         .remove::<Collidable>();
                 }
 
-                timer.tick(time.delta());
+                timer.tick(fixed_time.period);
 
                 if timer.finished() {
-                    *ship = Ship::spawn(Duration::from_secs(2));
+                    if lives.count(*player) > 0 {
+                        let protection = match *mode {
+                            GameMode::Versus => versus.spawn_protection,
+                            _ => Duration::from_secs(2),
+                        };
+                        *ship = Ship::spawn(protection);
+                        continue;
+                    }
+
+                    // Out of lives, the game goes on for as long as anyone else is still playing.
+                    commands.entity(entity).despawn();
+                    remaining -= 1;
+
+                    // Versus rounds are settled by the rules of the mode instead.
+                    if remaining > 0 || *mode == GameMode::Versus {
+                        continue;
+                    }
+
+                    if high_scores.qualifies(score.best()) {
+                        next_state.set(GameState::HighScoreEntry);
+                    } else {
+                        next_state.set(GameState::GameOver);
+                    }
                 }
             }
 
             ShipState::Spawning(ref mut timer) => {
                 if timer.elapsed().is_zero() {
+                    let (position, rotation) = match *mode {
+                        GameMode::Versus => {
+                            let others = positions
+                                .iter()
+                                .filter(|(other, _)| *other != entity)
+                                .map(|(_, position)| *position)
+                                .collect::<Vec<_>>();
+                            versus.respawn_point(*player, *player_count, &others)
+                        }
+                        _ => (spawn_position(*player, *player_count), 0.0),
+                    };
+
                     commands
                         .entity(entity)
                         .insert(ShapeBundle {
-                            path: GeometryBuilder::build_as(&{
-                                let mut path_builder = PathBuilder::new();
-                                path_builder.move_to(Vec2::ZERO);
-                                path_builder.line_to(Vec2::new(-8.0, -8.0));
-                                path_builder.line_to(Vec2::new(0.0, 12.0));
-                                path_builder.line_to(Vec2::new(8.0, -8.0));
-                                path_builder.line_to(Vec2::ZERO);
-                                let mut line = path_builder.build();
-                                line.0 = line.0.transformed(&Rotation::new(Angle::degrees(-90.0)));
-                                line
-                            }),
+                            path: ship_path(0.0),
                             ..Default::default()
                         })
-                        .insert(Stroke::new(Color::WHITE, 1.0))
-                        .insert(Transform::default())
+                        .insert(Stroke::new(player.color(), 1.0))
+                        .insert(
+                            Transform::from_translation(position.extend(0.0))
+                                .with_rotation(Quat::from_rotation_z(rotation)),
+                        )
                         .insert(Bounding::from_radius(12.0))
                         .insert(Velocity::default())
                         .insert(SpeedLimit::from(350.0))
                         .insert(Damping::from(0.998))
                         .insert(ThrustEngine::new(1.5))
+                        .insert(Shield::default())
                         .insert(AngularVelocity::default())
                         .insert(SteeringControl(Angle::degrees(180.0)))
                         .insert(BoundaryWrap)
                         .insert(Flick::new(Duration::from_millis(80)));
+
+                    // Versus ships are all on their own, so that their shots hit each other.
+                    if *mode != GameMode::Versus {
+                        commands.entity(entity).insert(Faction::Player);
+                    }
                 }
 
-                timer.tick(time.delta());
+                timer.tick(fixed_time.period);
 
                 if timer.finished() {
                     *ship = Ship::alive();
 
                     commands
                         .entity(entity)
-                        .insert(Weapon::new(Duration::from_millis(100)))
+                        .insert(
+                            Weapon::new(Duration::from_millis(100))
+                                .with_projectile(ProjectileProfile::classic()),
+                        )
                         .insert(Collidable)
                         .remove::<Flick>();
                 }
             }
-        }
-    }
-}
 
-fn ufo_state_system(
-    time: Res<Time>,
-    mut rng: Local<Random>,
-    mut commands: Commands,
-    mut ufos: Query<(Entity, &mut Weapon, &mut Ufo, &mut Velocity, &Transform), With<Ufo>>,
-    ships: Query<(Entity, &Ship), With<Ship>>,
-) {
-    let alive_ships = ships
-        .iter()
-        .filter_map(|(entity, ship)| match ship.state {
-            ShipState::Alive => Some(entity),
-            _ => None,
-        })
-        .collect::<Vec<_>>();
-
-    for (entity, mut weapon, mut ufo, mut velocity, transform) in ufos.iter_mut() {
-        match ufo.state {
-            UfoState::Alive(ref mut timer) => {
-                timer.tick(time.delta());
+            ShipState::Hyperspace(ref mut timer) => {
+                if timer.elapsed().is_zero() {
+                    if let Some(mut shield) = shield {
+                        shield.lower();
+                    }
 
-                if timer.finished() {
-                    *ufo =
-                        Ufo::changing_direction(Duration::from_millis(rng.gen_range(1000..2000)));
+                    commands
+                        .entity(entity)
+                        .insert(Velocity::default())
+                        .insert(AngularVelocity::default())
+                        .insert(Tween::new(timer.duration()).with_scale(
+                            Keyframes::new(Vec3::ONE).then(0.25, Vec3::ZERO, Ease::QuadIn),
+                        ))
+                        .remove::<SteeringControl>()
+                        .remove::<Weapon>()
+                        .remove::<ThrustEngine>()
+                        .remove::<Collidable>();
                 }
-            }
 
-            UfoState::ChangingDirection(ref mut timer) => {
-                if timer.elapsed().is_zero() {
-                    velocity.y = if transform.translation.y > 0.0 {
-                        -100.0
-                    } else {
-                        100.0
+                timer.tick(fixed_time.period);
+
+                if timer.finished() {
+                    let Some(mut transform) = transform else {
+                        continue;
                     };
-                }
 
-                timer.tick(time.delta());
+                    if let Ok(window) = primary_window.get_single() {
+                        let w = window.width() / 2.0;
+                        let h = window.height() / 2.0;
+                        transform.translation.x = rng.gen_range(-w..w);
+                        transform.translation.y = rng.gen_range(-h..h);
+                    }
+
+                    if rng.gen_bool(hyperspace.self_destruct_chance) {
+                        lives.lose(*player);
+                        kills.send(KillEvent {
+                            victim: *player,
+                            killer: None,
+                        });
+                        transform.scale = Vec3::ONE;
+                        *ship = Ship::dead(Duration::from_secs(2));
+                        commands.entity(entity).remove::<Tween>();
+                        spawn_ship_explosion(&mut commands, &mut **rng, transform.translation);
+                        continue;
+                    }
 
-                if timer.finished() {
-                    velocity.y = 0.0;
-                    *ufo = Ufo::alive(Duration::from_secs(rng.gen_range(4..8)));
+                    *ship = Ship::alive();
+
+                    commands
+                        .entity(entity)
+                        .insert(
+                            Tween::new(Duration::from_millis(200)).with_scale(Keyframes::from_to(
+                                Vec3::ZERO,
+                                Vec3::ONE,
+                                Ease::QuadOut,
+                            )),
+                        )
+                        .insert(ThrustEngine::new(1.5))
+                        .insert(SteeringControl(Angle::degrees(180.0)))
+                        .insert(
+                            Weapon::new(Duration::from_millis(100))
+                                .with_projectile(ProjectileProfile::classic()),
+                        )
+                        .insert(Collidable);
                 }
             }
         }
-
-        if let Some(ship) = alive_ships.choose(&mut **rng) {
-            weapon.triggered = true;
-            commands.entity(entity).insert(WeaponTarget(*ship));
-        } else {
-            weapon.triggered = false;
-            commands.entity(entity).remove::<WeaponTarget>();
-        }
     }
 }
 
 fn ufo_spawn_system(
     primary_window: Query<&Window, With<PrimaryWindow>>,
-    mut rng: Local<Random>,
+    definitions: Res<UfoDefinitions>,
+    score: Res<Score>,
+    mut rng: Local<RandomStream<UfoSpawn>>,
     mut commands: Commands,
-    ships: Query<Entity, With<Ship>>,
 ) {
-    if rng.gen_bool(1.0 / 10.0) {
-        let window = primary_window.single();
-        let h = (window.height() * 0.8) / 2.0;
-        let w = window.width() / 2.0;
+    // One roll picks among the definitions, each taking a share of it as large as its chance.
+    let mut roll = rng.gen::<f64>();
+    let Some(definition) = definitions.0.iter().find(|definition| {
+        roll -= definition.spawn_chance;
+        roll < 0.0
+    }) else {
+        return;
+    };
 
-        let y = rng.gen_range(-h..h);
-        let x = [-w, w].choose(&mut **rng).copied().unwrap();
-
-        let c = 30.0;
-        let position = Vec3::new(if x > 0.0 { w + c } else { -w - c }, y, 0.0);
-
-        let mut ufo = commands.spawn_empty();
-
-        ufo.insert(ShapeBundle {
-            path: GeometryBuilder::build_as(&{
-                let h = c / 2.5;
-                let w = c;
-                let hw = w / 2.0;
-                let hh = h / 2.0;
-
-                let mut path_builder = PathBuilder::new();
-                path_builder.move_to(Vec2::new(0.0, -hh));
-                path_builder.line_to(Vec2::new(-hw * 0.7, -hh));
-                path_builder.line_to(Vec2::new(-hw, 0.0));
-                path_builder.line_to(Vec2::new(-hw * 0.7, hh));
-                path_builder.line_to(Vec2::new(hw * 0.7, hh));
-                path_builder.line_to(Vec2::new(hw, 0.0));
-                path_builder.line_to(Vec2::new(hw * 0.7, -hh));
-                path_builder.line_to(Vec2::new(0.0, -hh));
-
-                path_builder.move_to(Vec2::new(-hw, 0.0));
-                path_builder.line_to(Vec2::new(hw, 0.0));
-
-                path_builder.move_to(Vec2::new(-hw * 0.5, hh));
-                path_builder.line_to(Vec2::new(-hw * 0.3, hh * 1.8));
-                path_builder.line_to(Vec2::new(hw * 0.3, hh * 1.8));
-                path_builder.line_to(Vec2::new(hw * 0.5, hh));
-
-                path_builder.build()
-            }),
+    let window = primary_window.single();
+    let h = (window.height() * 0.8) / 2.0;
+    let w = window.width() / 2.0;
+
+    let y = rng.gen_range(-h..h);
+    let x = [-w, w].choose(&mut **rng).copied().unwrap();
+
+    let c = definition.size;
+    let cruise =
+        Vec2::new(if x > 0.0 { -1.0 } else { 1.0 }, 0.0) * rng.gen_range(definition.speed.clone());
+    let position = Vec3::new(if x > 0.0 { w + c } else { -w - c }, y, 0.0);
+
+    let mut ufo = commands.spawn_empty();
+
+    ufo.insert(ShapeBundle {
+        path: GeometryBuilder::build_as(&{
+            let h = c / 2.5;
+            let w = c;
+            let hw = w / 2.0;
+            let hh = h / 2.0;
+
+            let mut path_builder = PathBuilder::new();
+            path_builder.move_to(Vec2::new(0.0, -hh));
+            path_builder.line_to(Vec2::new(-hw * 0.7, -hh));
+            path_builder.line_to(Vec2::new(-hw, 0.0));
+            path_builder.line_to(Vec2::new(-hw * 0.7, hh));
+            path_builder.line_to(Vec2::new(hw * 0.7, hh));
+            path_builder.line_to(Vec2::new(hw, 0.0));
+            path_builder.line_to(Vec2::new(hw * 0.7, -hh));
+            path_builder.line_to(Vec2::new(0.0, -hh));
+
+            path_builder.move_to(Vec2::new(-hw, 0.0));
+            path_builder.line_to(Vec2::new(hw, 0.0));
+
+            match definition.kind {
+                UfoKind::Large => {
+                    path_builder.move_to(Vec2::new(-hw * 0.5, hh));
+                    path_builder.line_to(Vec2::new(-hw * 0.3, hh * 1.8));
+                    path_builder.line_to(Vec2::new(hw * 0.3, hh * 1.8));
+                    path_builder.line_to(Vec2::new(hw * 0.5, hh));
+                }
+                UfoKind::Small => {
+                    path_builder.move_to(Vec2::new(-hw * 0.4, hh));
+                    path_builder.line_to(Vec2::new(0.0, hh * 2.4));
+                    path_builder.line_to(Vec2::new(hw * 0.4, hh));
+                }
+            }
+
+            path_builder.build()
+        }),
+        ..Default::default()
+    })
+    .insert(Stroke::new(Color::WHITE, 1.0))
+    .insert(Transform::from_translation(position))
+    .insert(Ufo {
+        kind: definition.kind,
+    })
+    .insert(StateScoped(GameState::Playing))
+    .insert(Faction::Enemy)
+    .insert(definition.accuracy.aim(score.best()))
+    .insert(Weapon {
+        projectile: ProjectileProfile {
+            speed: rng.gen_range(definition.projectile_speed.clone()),
+            lifetime: Some(Duration::from_secs(3)),
             ..Default::default()
-        })
-        .insert(Stroke::new(Color::WHITE, 1.0))
-        .insert(Transform::from_translation(position))
-        .insert(Ufo::alive(Duration::from_secs(rng.gen_range(1..5))))
-        .insert(Weapon {
-            force: rng.gen_range(300.0..500.0),
-            triggered: true,
-            automatic: true,
-            ..Weapon::new(Duration::from_millis(rng.gen_range(1000..3000)))
-        })
-        .insert(Bounding::from_radius(c / 2.0))
-        .insert(Velocity::from(
-            Vec2::new(if x > 0.0 { -1.0 } else { 1.0 }, 0.0) * rng.gen_range(100.0..200.0),
+        },
+        automatic: true,
+        ..Weapon::new(Duration::from_millis(
+            rng.gen_range(definition.rate_of_fire.clone()),
         ))
-        .insert(Collidable)
-        .insert(BoundaryRemoval);
-
-        if let Some(ship) = ships.iter().collect::<Vec<_>>().choose(&mut **rng) {
-            ufo.insert(WeaponTarget(*ship));
-        }
-    }
+    })
+    .insert(Bounding::from_radius(c / 2.0))
+    .insert(Velocity::from(cruise))
+    .insert(Collidable)
+    .insert(BoundaryRemoval)
+    .insert(
+        Steering::new(cruise.length() * 1.5, 300.0)
+            .with(Behavior::Cruise(cruise), 1.0)
+            .with_all(definition.behaviors.iter().copied()),
+    )
+    .insert(Brain::new(
+        definition.brain.clone(),
+        Duration::from_millis(250),
+    ));
 }
 
 fn asteroid_spawn_system(
     primary_window: Query<&Window, With<PrimaryWindow>>,
     asteroid_sizes: Res<AsteroidSizes>,
-    mut rng: Local<Random>,
+    mut rng: Local<RandomStream<AsteroidSpawn>>,
     mut asteroids: EventWriter<AsteroidSpawnEvent>,
 ) {
     if rng.gen_bool(1.0 / 3.0) {
@@ -540,29 +1178,39 @@ fn asteroid_spawn_system(
             Vec2::new(if x > 0.0 { w + c } else { -w - c }, y)
         };
 
-        asteroids.send(AsteroidSpawnEvent(position, Bounding::from_radius(radius)));
+        asteroids.send(AsteroidSpawnEvent(
+            position,
+            Bounding::from_radius(radius),
+            None,
+        ));
     }
 }
 
 fn asteroid_generation_system(
     primary_window: Query<&Window, With<PrimaryWindow>>,
     asteroid_sizes: Res<AsteroidSizes>,
-    mut rng: Local<Random>,
+    mode: Res<GameMode>,
+    wave: Res<Wave>,
+    wave_settings: Res<WaveSettings>,
+    mut rng: Local<RandomStream<AsteroidShape>>,
     mut asteroids: EventReader<AsteroidSpawnEvent>,
+    mut piercing: Query<&mut Piercing>,
     mut commands: Commands,
 ) {
     let window = primary_window.single();
     let w = window.width() / 2.0;
     let h = window.height() / 2.0;
 
-    for AsteroidSpawnEvent(position, bounds) in asteroids.iter() {
+    for AsteroidSpawnEvent(position, bounds, splitter) in asteroids.iter() {
         let velocity = Vec2::new(rng.gen_range(-w..w), rng.gen_range(-h..h));
-        let scale = if asteroid_sizes.big.contains(bounds) {
-            rng.gen_range(30.0..60.0)
-        } else if asteroid_sizes.medium.contains(bounds) {
-            rng.gen_range(60.0..80.0)
-        } else {
-            rng.gen_range(80.0..100.0)
+        let scale = match asteroid_sizes.classify(bounds) {
+            AsteroidSize::Big => rng.gen_range(30.0..60.0),
+            AsteroidSize::Medium => rng.gen_range(60.0..80.0),
+            AsteroidSize::Small => rng.gen_range(80.0..100.0),
+        };
+        let scale = match *mode {
+            GameMode::Waves => scale * wave.speed_scale(&wave_settings),
+            GameMode::Endless | GameMode::Versus => scale,
         };
         let velocity = (velocity - *position).normalize_or_zero() * scale;
 
@@ -586,7 +1234,7 @@ fn asteroid_generation_system(
             }
         };
 
-        commands
+        let asteroid = commands
             .spawn(ShapeBundle {
                 path: GeometryBuilder::build_as(&shape),
                 ..Default::default()
@@ -596,128 +1244,488 @@ fn asteroid_generation_system(
                 position.x, position.y, 0.0,
             )))
             .insert(Asteroid)
+            .insert(Obstacle)
+            .insert(StateScoped(GameState::Playing))
             .insert(Collidable)
             .insert(*bounds)
             .insert(Velocity::from(velocity))
             .insert(AngularVelocity::from(rng.gen_range(-3.0..3.0)))
-            .insert(BoundaryRemoval);
+            .id();
+
+        match *mode {
+            GameMode::Waves => commands.entity(asteroid).insert(BoundaryWrap),
+            GameMode::Endless | GameMode::Versus => {
+                commands.entity(asteroid).insert(BoundaryRemoval)
+            }
+        };
+
+        if let Some(mut piercing) = splitter.and_then(|bullet| piercing.get_mut(bullet).ok()) {
+            piercing.pass(asteroid);
+        }
+    }
+}
+
+fn input_controls_system(actions: Res<Actions>, mut controls: ResMut<Controls>) {
+    let mut frames = [ControlFrame::default(); MAX_PLAYERS];
+    let mut aim = [None; MAX_PLAYERS];
+    for player in 0..MAX_PLAYERS {
+        frames[player] = actions.controls(Player(player));
+        aim[player] = actions.aim(Player(player));
     }
+
+    controls.advance(frames, aim);
 }
 
 fn steering_control_system(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<(&mut AngularVelocity, &SteeringControl)>,
+    mut query: Query<(&ShipCommands, &mut AngularVelocity, &SteeringControl)>,
 ) {
-    for (mut angular_velocity, steering) in query.iter_mut() {
-        if keyboard_input.pressed(KeyCode::Left) {
-            *angular_velocity = AngularVelocity::from(steering.0.get());
-        } else if keyboard_input.pressed(KeyCode::Right) {
-            *angular_velocity = AngularVelocity::from(-steering.0.get());
-        } else {
-            *angular_velocity = AngularVelocity::from(0.0);
+    for (commands, mut angular_velocity, steering) in query.iter_mut() {
+        *angular_velocity = AngularVelocity::from(steering.0.get() * commands.steering());
+    }
+}
+
+fn thrust_control_system(mut query: Query<(&ShipCommands, &mut ThrustEngine)>) {
+    for (commands, mut thrust_engine) in query.iter_mut() {
+        // Only touch the engine when the throttle moves, its flame is redrawn on every change.
+        let throttle = commands.throttle();
+        if thrust_engine.throttle != throttle {
+            thrust_engine.throttle = throttle;
         }
     }
 }
 
-fn thrust_control_system(keyboard_input: Res<Input<KeyCode>>, mut query: Query<&mut ThrustEngine>) {
-    for mut thrust_engine in query.iter_mut() {
-        thrust_engine.on = keyboard_input.pressed(KeyCode::Up)
+fn hyperspace_control_system(
+    hyperspace: Res<HyperspaceSettings>,
+    mut query: Query<(&ShipCommands, &mut Ship)>,
+) {
+    for (commands, mut ship) in query.iter_mut() {
+        if commands.just_pressed(Control::Hyperspace) && matches!(ship.state, ShipState::Alive) {
+            *ship = Ship::hyperspace(hyperspace.delay);
+        }
     }
 }
 
-fn weapon_control_system(keyboard_input: Res<Input<KeyCode>>, mut query: Query<&mut Weapon>) {
-    for mut weapon in query.iter_mut() {
+fn shield_control_system(mut query: Query<(&ShipCommands, &mut Shield)>) {
+    for (commands, mut shield) in query.iter_mut() {
+        if commands.just_pressed(Control::Shield) {
+            shield.toggle();
+        }
+    }
+}
+
+fn weapon_control_system(mut query: Query<(&ShipCommands, &mut Weapon)>) {
+    for (commands, mut weapon) in query.iter_mut() {
         let pressed = if weapon.automatic {
-            keyboard_input.pressed(KeyCode::Space)
+            commands.pressed(Control::Fire)
         } else {
-            keyboard_input.just_pressed(KeyCode::Space)
+            commands.just_pressed(Control::Fire)
         };
         weapon.triggered = weapon.triggered || pressed;
     }
 }
 
-fn explosion_system(mut query: Query<&mut Transform, With<Explosion>>) {
-    for mut transform in query.iter_mut() {
-        transform.scale.x += 0.001;
-        transform.scale.y += 0.001;
-        transform.scale.z += 0.001;
+fn spawn_screen(commands: &mut Commands, state: GameState, lines: &[(&str, f32)]) -> Entity {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            StateScoped(state),
+        ))
+        .with_children(|parent| {
+            for (line, font_size) in lines {
+                parent.spawn(TextBundle::from_section(
+                    *line,
+                    TextStyle {
+                        font_size: *font_size,
+                        color: Color::WHITE,
+                        ..Default::default()
+                    },
+                ));
+            }
+        })
+        .id()
+}
+
+fn main_menu_setup_system(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    player_count: Res<PlayerCount>,
+    cpu_players: Res<CpuPlayers>,
+) {
+    let screen = spawn_screen(
+        &mut commands,
+        GameState::MainMenu,
+        &[
+            ("BEVYROIDS", 72.0),
+            ("Press Enter to start", 24.0),
+            ("Press B to change controls", 20.0),
+        ],
+    );
+
+    commands.entity(screen).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section(
+                mode_label(*mode),
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::GRAY,
+                    ..Default::default()
+                },
+            ),
+            ModeText,
+        ));
+        parent.spawn((
+            TextBundle::from_section(
+                players_label(*player_count, *cpu_players),
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::GRAY,
+                    ..Default::default()
+                },
+            ),
+            PlayersText,
+        ));
+    });
+}
+
+fn mode_label(mode: GameMode) -> String {
+    let name = match mode {
+        GameMode::Waves => "WAVES",
+        GameMode::Endless => "ENDLESS",
+        GameMode::Versus => "VERSUS",
+    };
+    format!("Mode: {name} (M to change)")
+}
+
+fn players_label(count: PlayerCount, cpu: CpuPlayers) -> String {
+    let rivals = if *cpu { "CPU" } else { "LOCAL" };
+    format!(
+        "Players: {} {rivals} (1-{MAX_PLAYERS} to change, C to toggle CPU)",
+        *count
+    )
+}
+
+fn main_menu_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut mode: ResMut<GameMode>,
+    mut player_count: ResMut<PlayerCount>,
+    mut cpu_players: ResMut<CpuPlayers>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut texts: Query<&mut Text, With<ModeText>>,
+    mut players_texts: Query<&mut Text, (With<PlayersText>, Without<ModeText>)>,
+) {
+    if keyboard_input.just_pressed(KeyCode::M) {
+        *mode = match *mode {
+            GameMode::Waves => GameMode::Endless,
+            GameMode::Endless => GameMode::Versus,
+            GameMode::Versus => GameMode::Waves,
+        };
+        for mut text in texts.iter_mut() {
+            text.sections[0].value = mode_label(*mode);
+        }
+    }
+
+    let keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+    if let Some(count) = keys
+        .iter()
+        .position(|key| keyboard_input.just_pressed(*key))
+    {
+        *player_count = PlayerCount::new(count + 1);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::C) {
+        **cpu_players = !**cpu_players;
+    }
+
+    if player_count.is_changed() || cpu_players.is_changed() {
+        for mut text in players_texts.iter_mut() {
+            text.sections[0].value = players_label(*player_count, *cpu_players);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::B) {
+        next_state.set(GameState::Rebinding);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        if *mode == GameMode::Versus && **player_count < 2 {
+            *player_count = PlayerCount::new(2);
+        }
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn pause_setup_system(mut commands: Commands) {
+    spawn_screen(
+        &mut commands,
+        GameState::Paused,
+        &[
+            ("PAUSED", 64.0),
+            ("Press P to resume", 24.0),
+            ("Press Q to quit to the menu", 24.0),
+        ],
+    );
+}
+
+fn pause_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let toggled = actions.any_just_pressed(controls::Action::Pause);
+
+    match state.get() {
+        GameState::Playing if toggled => next_state.set(GameState::Paused),
+        GameState::Paused if toggled => next_state.set(GameState::Playing),
+        GameState::Paused if keyboard_input.just_pressed(KeyCode::Q) => {
+            next_state.set(GameState::MainMenu)
+        }
+        _ => {}
+    }
+}
+
+fn game_over_setup_system(mut commands: Commands, high_scores: Res<HighScores>) {
+    let table = high_scores
+        .iter()
+        .enumerate()
+        .map(|(rank, entry)| {
+            let wave = match entry.wave {
+                0 => "-".to_string(),
+                wave => wave.to_string(),
+            };
+            format!(
+                "{:>2}. {} {:>7} {:>3}  {}",
+                rank + 1,
+                entry.initials,
+                entry.score,
+                wave,
+                entry.date
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![("GAME OVER", 64.0)];
+    lines.extend(table.iter().map(|line| (line.as_str(), 20.0)));
+    lines.push(("Press Enter to restart", 24.0));
+    lines.push(("Press Escape to return to the menu", 24.0));
+
+    spawn_screen(&mut commands, GameState::GameOver, &lines);
+}
+
+fn game_over_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        next_state.set(GameState::Playing);
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn new_game_system(
+    player_count: Res<PlayerCount>,
+    cpu_players: Res<CpuPlayers>,
+    replay: Option<Res<Replay>>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut extra_lives: ResMut<ExtraLives>,
+    mut wave: ResMut<Wave>,
+    mut standings: ResMut<Standings>,
+    mut commands: Commands,
+) {
+    standings.reset_round();
+    score.reset();
+    lives.reset();
+    extra_lives.reset();
+    wave.reset();
+
+    for player in player_count.players() {
+        let source = if replay.is_some() {
+            ControlSource::Replay(*player)
+        } else if **cpu_players && *player > 0 {
+            ControlSource::Bot
+        } else {
+            ControlSource::Local(player)
+        };
+
+        commands.spawn((
+            Ship::spawn(Duration::from_secs(0)),
+            player,
+            source,
+            ShipCommands::default(),
+            StateScoped(GameState::Playing),
+        ));
+    }
+}
+
+/// Where the ship of the player appears, side by side with the others around the center.
+fn spawn_position(player: Player, count: PlayerCount) -> Vec2 {
+    let offset = *player as f32 - (*count - 1) as f32 / 2.0;
+    Vec2::new(offset * 60.0, 0.0)
+}
+
+fn clock_control_system(keyboard_input: Res<Input<KeyCode>>, mut clock: ResMut<GameClock>) {
+    if keyboard_input.just_pressed(KeyCode::F1) {
+        clock.toggle_pause();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::N) {
+        clock.step();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Minus) {
+        let scale = clock.scale() / 2.0;
+        clock.set_scale(scale);
+    } else if keyboard_input.just_pressed(KeyCode::Equals) {
+        let scale = clock.scale() * 2.0;
+        clock.set_scale(scale);
     }
 }
 
 fn ship_hit_system(
-    mut rng: Local<Random>,
+    mut rng: Local<RandomStream<ShipExplosion>>,
     mut asteroid_hits: EventReader<HitEvent<Asteroid, Ship>>,
     mut bullet_hits: EventReader<HitEvent<Bullet, Ship>>,
     mut ufo_hits: EventReader<HitEvent<Ufo, Ship>>,
+    mut lives: ResMut<Lives>,
+    mut kills: EventWriter<KillEvent>,
     mut commands: Commands,
-    query: Query<&Transform, With<Ship>>,
+    query: Query<(&Transform, &Player, &Ship, Option<&Shield>)>,
+    players: Query<&Player>,
 ) {
+    let mut killed = HashSet::new();
+
+    // Asteroids and bullets are deflected by an active shield, UFOs are not.
     let hits = asteroid_hits
         .iter()
-        .map(|hit| hit.hurtable())
-        .chain(bullet_hits.iter().map(|hit| hit.hurtable()))
-        .chain(ufo_hits.iter().map(|hit| hit.hurtable()));
+        .map(|hit| (hit.hurtable(), true, None))
+        .chain(
+            bullet_hits
+                .iter()
+                .map(|hit| (hit.hurtable(), true, hit.shooter())),
+        )
+        .chain(ufo_hits.iter().map(|hit| (hit.hurtable(), false, None)));
 
-    for ship in hits {
-        if let Ok(transform) = query.get(ship) {
-            for n in 0..12 * 6 {
-                let angle = 2.0 * PI / 12.0 * (n % 12) as f32 + rng.gen_range(0.0..2.0 * PI / 12.0);
-                let direction = Vec3::new(angle.cos(), angle.sin(), 0.0);
-                let position = direction * rng.gen_range(1.0..20.0) + transform.translation;
+    for (ship_entity, shieldable, shooter) in hits {
+        if killed.contains(&ship_entity) {
+            continue;
+        }
 
-                commands
-                    .entity(ship)
-                    .insert(Ship::dead(Duration::from_secs(2)));
+        if let Ok((transform, player, ship, shield)) = query.get(ship_entity) {
+            if !matches!(ship.state, ShipState::Alive) {
+                continue;
+            }
 
-                commands
-                    .spawn(ExplosionBundle::default())
-                    .insert(Transform::from_translation(position))
-                    .insert(Velocity::from(
-                        Vec2::new(angle.cos(), angle.sin()) * rng.gen_range(150.0..250.0),
-                    ))
-                    .insert(Expiration::new(Duration::from_millis(
-                        rng.gen_range(1000..1500),
-                    )))
-                    .insert(Flick::new(Duration::from_millis(rng.gen_range(20..30))));
+            if shieldable && shield.is_some_and(Shield::active) {
+                continue;
             }
+
+            killed.insert(ship_entity);
+            lives.lose(*player);
+            kills.send(KillEvent {
+                victim: *player,
+                killer: shooter.and_then(|shooter| players.get(shooter).ok().copied()),
+            });
+
+            commands
+                .entity(ship_entity)
+                .insert(Ship::dead(Duration::from_secs(2)));
+
+            spawn_ship_explosion(&mut commands, &mut **rng, transform.translation);
         }
     }
 }
 
+fn spawn_ship_explosion(commands: &mut Commands, rng: &mut impl Rng, center: Vec3) {
+    for n in 0..12 * 6 {
+        let angle = 2.0 * PI / 12.0 * (n % 12) as f32 + rng.gen_range(0.0..2.0 * PI / 12.0);
+        let direction = Vec3::new(angle.cos(), angle.sin(), 0.0);
+        let position = direction * rng.gen_range(1.0..20.0) + center;
+
+        commands
+            .spawn(ExplosionBundle::default())
+            .insert(Transform::from_translation(position))
+            .insert(Velocity::from(
+                Vec2::new(angle.cos(), angle.sin()) * rng.gen_range(150.0..250.0),
+            ))
+            .insert(Expiration::new(Duration::from_millis(
+                rng.gen_range(1000..1500),
+            )))
+            .insert(Flick::new(Duration::from_millis(rng.gen_range(20..30))));
+    }
+}
+
 fn asteroid_hit_system(
     asteroid_sizes: Res<AsteroidSizes>,
-    mut rng: Local<Random>,
+    points: Res<PointValues>,
+    mode: Res<GameMode>,
+    mut scores: EventWriter<ScoreEvent>,
+    mut rng: Local<RandomStream<AsteroidExplosion>>,
     mut asteroid_hits: EventReader<HitEvent<Bullet, Asteroid>>,
     mut asteroid_spawn: EventWriter<AsteroidSpawnEvent>,
+    mut drops: EventWriter<PowerUpDropEvent>,
     mut commands: Commands,
     query: Query<(&Transform, &Bounding), With<Asteroid>>,
+    piercing: Query<&Piercing>,
+    players: Query<&Player>,
 ) {
     let mut removed = HashSet::with_capacity(asteroid_hits.len());
 
     for hit in asteroid_hits.iter() {
         let asteroid = hit.hurtable();
         let bullet = hit.hittable();
+        let by_player = hit.shooter().and_then(|shooter| players.get(shooter).ok());
 
         if removed.contains(&asteroid) || removed.contains(&bullet) {
             continue;
         }
 
+        let piercing = piercing.get(bullet).ok();
+        if piercing.is_some_and(|piercing| piercing.passes(asteroid)) {
+            continue;
+        }
+        let splitter = piercing.map(|_| bullet);
+
         if let Ok((transform, radius)) = query.get(asteroid) {
             let position = Vec2::new(transform.translation.x, transform.translation.y);
 
-            let explosion_size = if asteroid_sizes.big.contains(radius) {
-                let bounds = Bounding::from_radius(rng.gen_range(asteroid_sizes.medium.clone()));
-                asteroid_spawn.send(AsteroidSpawnEvent(position, bounds));
-                asteroid_spawn.send(AsteroidSpawnEvent(position, bounds));
-                5
-            } else if asteroid_sizes.medium.contains(radius) {
-                let bounds = Bounding::from_radius(rng.gen_range(asteroid_sizes.small.clone()));
-                asteroid_spawn.send(AsteroidSpawnEvent(position, bounds));
-                asteroid_spawn.send(AsteroidSpawnEvent(position, bounds));
-                asteroid_spawn.send(AsteroidSpawnEvent(position, bounds));
-                3
-            } else {
-                1
+            let size = asteroid_sizes.classify(radius);
+            if let Some(player) = by_player {
+                // Points would earn extra lives, which versus rounds have no place for.
+                if *mode != GameMode::Versus {
+                    scores.send(ScoreEvent(*player, points.asteroid(size)));
+                }
+                drops.send(PowerUpDropEvent(position, DropSource::Asteroid));
+            }
+
+            let explosion_size = match size {
+                AsteroidSize::Big => {
+                    let bounds =
+                        Bounding::from_radius(rng.gen_range(asteroid_sizes.medium.clone()));
+                    asteroid_spawn.send(AsteroidSpawnEvent(position, bounds, splitter));
+                    asteroid_spawn.send(AsteroidSpawnEvent(position, bounds, splitter));
+                    5
+                }
+                AsteroidSize::Medium => {
+                    let bounds = Bounding::from_radius(rng.gen_range(asteroid_sizes.small.clone()));
+                    asteroid_spawn.send(AsteroidSpawnEvent(position, bounds, splitter));
+                    asteroid_spawn.send(AsteroidSpawnEvent(position, bounds, splitter));
+                    asteroid_spawn.send(AsteroidSpawnEvent(position, bounds, splitter));
+                    3
+                }
+                AsteroidSize::Small => 1,
             };
 
             for n in 0..12 * explosion_size {
@@ -739,33 +1747,54 @@ fn asteroid_hit_system(
         }
 
         commands.entity(asteroid).despawn();
-        commands.entity(bullet).despawn();
-
         removed.insert(asteroid);
-        removed.insert(bullet);
+
+        if piercing.is_none() {
+            commands.entity(bullet).despawn();
+            removed.insert(bullet);
+        }
     }
 }
 
 fn ufo_hit_system(
-    mut rng: Local<Random>,
+    definitions: Res<UfoDefinitions>,
+    mut scores: EventWriter<ScoreEvent>,
+    mut rng: Local<RandomStream<UfoExplosion>>,
     mut bullet_hits: EventReader<HitEvent<Bullet, Ufo>>,
     mut asteroid_hits: EventReader<HitEvent<Asteroid, Ufo>>,
+    mut drops: EventWriter<PowerUpDropEvent>,
     mut commands: Commands,
-    query: Query<&Transform, With<Ufo>>,
+    query: Query<(&Transform, &Ufo)>,
+    piercing: Query<(), With<Piercing>>,
+    players: Query<&Player>,
 ) {
     let mut removed = HashSet::with_capacity(bullet_hits.len() + asteroid_hits.len());
+    let mut bullets = Vec::new();
 
     let hits = bullet_hits
         .iter()
-        .map(|hit| hit.hurtable())
-        .chain(asteroid_hits.iter().map(|hit| hit.hurtable()));
+        .map(|hit| (hit.hurtable(), Some(hit.hittable()), hit.shooter()))
+        .chain(asteroid_hits.iter().map(|hit| (hit.hurtable(), None, None)));
+
+    for (ufo, bullet, shooter) in hits {
+        bullets.extend(bullet);
 
-    for ufo in hits {
         if removed.contains(&ufo) {
             continue;
         }
 
-        if let Ok(transform) = query.get(ufo) {
+        if let Ok((transform, ufo)) = query.get(ufo) {
+            if let Some(player) = shooter.and_then(|shooter| players.get(shooter).ok()) {
+                let points = definitions
+                    .get(ufo.kind)
+                    .map_or(0, |definition| definition.points);
+                scores.send(ScoreEvent(*player, points));
+                drops.send(PowerUpDropEvent(
+                    transform.translation.truncate(),
+                    DropSource::Ufo,
+                ));
+            }
+
             for n in 0..12 * 5 {
                 let angle = 2.0 * PI / 12.0 * (n % 12) as f32 + rng.gen_range(0.0..2.0 * PI / 12.0);
                 let direction = Vec3::new(angle.cos(), angle.sin(), 0.0);
@@ -788,9 +1817,8 @@ fn ufo_hit_system(
         removed.insert(ufo);
     }
 
-    for hit in bullet_hits.iter() {
-        let bullet = hit.hittable();
-        if removed.contains(&bullet) {
+    for bullet in bullets {
+        if removed.contains(&bullet) || piercing.contains(bullet) {
             continue;
         }
         commands.entity(bullet).despawn();
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+/// Most local players that can share a game.
+pub const MAX_PLAYERS: usize = 4;
+
+pub struct PlayersPlugin {
+    count: usize,
+}
+
+impl PlayersPlugin {
+    pub fn new(count: usize) -> Self {
+        Self { count }
+    }
+}
+
+impl Plugin for PlayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayerCount::new(self.count))
+            .init_resource::<CpuPlayers>();
+    }
+}
+
+/// Local player controlling a ship, from `0` up to [`MAX_PLAYERS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component, Deref)]
+pub struct Player(pub usize);
+
+impl Player {
+    pub fn color(self) -> Color {
+        match self.0 {
+            0 => Color::WHITE,
+            1 => Color::rgb(0.4, 0.8, 1.0),
+            2 => Color::rgb(1.0, 0.85, 0.3),
+            _ => Color::rgb(1.0, 0.45, 0.8),
+        }
+    }
+
+    pub fn label(self) -> String {
+        format!("P{}", self.0 + 1)
+    }
+}
+
+/// Number of players taking part in the next game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource, Deref)]
+pub struct PlayerCount(usize);
+
+impl PlayerCount {
+    pub fn new(count: usize) -> Self {
+        Self(count.clamp(1, MAX_PLAYERS))
+    }
+
+    pub fn players(self) -> impl Iterator<Item = Player> {
+        (0..self.0).map(Player)
+    }
+}
+
+/// Whether every player but the first is driven by the computer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource, Deref, DerefMut)]
+pub struct CpuPlayers(pub bool);
@@ -0,0 +1,384 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+use rand::{prelude::SliceRandom, Rng};
+
+use crate::{
+    boundary::{BoundaryWrap, Bounding},
+    collision::{Collidable, CollisionSystemLabel, HitEvent},
+    expiration::Expiration,
+    flickering::Flick,
+    lives::Lives,
+    physics::Velocity,
+    players::{Player, PlayerCount, MAX_PLAYERS},
+    random::{RandomStream, StreamLabel},
+    shield::Shield,
+    state::{GameState, StateScoped, StateVisible},
+    tween::{Ease, Keyframes, Repeat, Tween},
+    waves::GameMode,
+    Ship, Weapon,
+};
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerUpSettings>()
+            .add_event::<PowerUpDropEvent>()
+            .add_systems(Startup, power_up_hud_setup_system)
+            .add_systems(
+                FixedUpdate,
+                (
+                    drop_system,
+                    pickup_flick_system,
+                    collect_system.after(CollisionSystemLabel),
+                    power_up_system.after(collect_system),
+                )
+                    .in_set(PowerUpSystemLabel),
+            )
+            .add_systems(PostUpdate, power_up_hud_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct PowerUpSystemLabel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUpKind {
+    /// Fires three bullets in a spread.
+    TripleShot,
+    /// Shortens the weapon cooldown.
+    RapidFire,
+    /// Bullets keep going after destroying what they hit.
+    Piercing,
+    /// Keeps the shield up at full charge.
+    Shield,
+    /// Awards a life on pickup.
+    ExtraLife,
+}
+
+impl PowerUpKind {
+    const ALL: [PowerUpKind; 5] = [
+        PowerUpKind::TripleShot,
+        PowerUpKind::RapidFire,
+        PowerUpKind::Piercing,
+        PowerUpKind::Shield,
+        PowerUpKind::ExtraLife,
+    ];
+
+    fn color(self) -> Color {
+        match self {
+            PowerUpKind::TripleShot => Color::ORANGE,
+            PowerUpKind::RapidFire => Color::YELLOW,
+            PowerUpKind::Piercing => Color::FUCHSIA,
+            PowerUpKind::Shield => Color::rgb(0.3, 0.7, 1.0),
+            PowerUpKind::ExtraLife => Color::GREEN,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PowerUpKind::TripleShot => "TRIPLE",
+            PowerUpKind::RapidFire => "RAPID",
+            PowerUpKind::Piercing => "PIERCE",
+            PowerUpKind::Shield => "SHIELD",
+            PowerUpKind::ExtraLife => "LIFE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct PowerUpSettings {
+    pub asteroid_drop_chance: f64,
+    pub ufo_drop_chance: f64,
+    /// How long a pickup stays in the arena.
+    pub lifetime: Duration,
+    /// How long before expiring a pickup starts flickering.
+    pub warning: Duration,
+    /// How long a timed effect lasts once collected.
+    pub duration: Duration,
+    /// Cooldown divisor applied by rapid fire.
+    pub rapid_fire_factor: u32,
+    /// Angle between the bullets of a triple shot, in radians.
+    pub triple_shot_spread: f32,
+}
+
+impl Default for PowerUpSettings {
+    fn default() -> Self {
+        Self {
+            asteroid_drop_chance: 0.04,
+            ufo_drop_chance: 0.5,
+            lifetime: Duration::from_secs(10),
+            warning: Duration::from_secs(3),
+            duration: Duration::from_secs(10),
+            rapid_fire_factor: 3,
+            triple_shot_spread: PI / 12.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropSource {
+    Asteroid,
+    Ufo,
+}
+
+/// Something was destroyed at the given position and may leave a pickup behind.
+#[derive(Debug, Event)]
+pub struct PowerUpDropEvent(pub Vec2, pub DropSource);
+
+/// A pickup waiting to be collected.
+#[derive(Debug, Component)]
+pub struct PowerUp(PowerUpKind);
+
+/// Timed effects currently held by a ship.
+#[derive(Debug, Default, Component)]
+pub struct PowerUps {
+    active: Vec<(PowerUpKind, Timer)>,
+}
+
+impl PowerUps {
+    pub fn has(&self, kind: PowerUpKind) -> bool {
+        self.active.iter().any(|(active, _)| *active == kind)
+    }
+
+    /// Starts the effect, or restarts it when already held.
+    fn grant(&mut self, kind: PowerUpKind, duration: Duration) {
+        let timer = Timer::new(duration, TimerMode::Once);
+        match self.active.iter_mut().find(|(active, _)| *active == kind) {
+            Some((_, existing)) => *existing = timer,
+            None => self.active.push((kind, timer)),
+        }
+    }
+}
+
+/// Bullets that survive hitting their target.
+#[derive(Debug, Component, Default)]
+pub struct Piercing {
+    /// Fragments of the asteroids the bullet split, which it goes through unharmed.
+    passed: Vec<Entity>,
+}
+
+impl Piercing {
+    pub fn pass(&mut self, entity: Entity) {
+        self.passed.push(entity);
+    }
+
+    pub fn passes(&self, entity: Entity) -> bool {
+        self.passed.contains(&entity)
+    }
+}
+
+struct PowerUpDrop;
+
+impl StreamLabel for PowerUpDrop {
+    const LABEL: &'static str = "power_up_drop";
+}
+
+/// Power-ups of a player, one line under the other.
+#[derive(Debug, Component)]
+struct PowerUpText(Player);
+
+fn drop_system(
+    settings: Res<PowerUpSettings>,
+    mode: Res<GameMode>,
+    mut rng: Local<RandomStream<PowerUpDrop>>,
+    mut drops: EventReader<PowerUpDropEvent>,
+    mut commands: Commands,
+) {
+    for PowerUpDropEvent(position, source) in drops.iter() {
+        let chance = match source {
+            DropSource::Asteroid => settings.asteroid_drop_chance,
+            DropSource::Ufo => settings.ufo_drop_chance,
+        };
+        if !rng.gen_bool(chance) {
+            continue;
+        }
+
+        // Versus rounds last until a single ship is left, which extra lives would drag out.
+        let kinds = PowerUpKind::ALL
+            .iter()
+            .filter(|kind| *mode != GameMode::Versus || **kind != PowerUpKind::ExtraLife)
+            .collect::<Vec<_>>();
+        let kind = **kinds.choose(&mut **rng).unwrap();
+        let angle = rng.gen_range(0.0..2.0 * PI);
+        let speed = rng.gen_range(20.0..40.0);
+
+        commands.spawn((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shapes::RegularPolygon {
+                    sides: 6,
+                    feature: shapes::RegularPolygonFeature::Radius(8.0),
+                    ..Default::default()
+                }),
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..Default::default()
+            },
+            Stroke::new(kind.color(), 1.5),
+            PowerUp(kind),
+            Collidable,
+            Bounding::from_radius(8.0),
+            Velocity::from(Vec2::new(angle.cos(), angle.sin()) * speed),
+            BoundaryWrap,
+            Expiration::new(settings.lifetime),
+            Tween::new(Duration::from_secs(3))
+                .with_repeat(Repeat::Loop)
+                .with_rotation(Keyframes::from_to(0.0, 2.0 * PI, Ease::Linear)),
+            StateScoped(GameState::Playing),
+        ));
+    }
+}
+
+fn pickup_flick_system(
+    settings: Res<PowerUpSettings>,
+    mut commands: Commands,
+    query: Query<(Entity, &Expiration), (With<PowerUp>, Without<Flick>)>,
+) {
+    for (entity, expiration) in query.iter() {
+        if expiration.remaining() <= settings.warning {
+            commands
+                .entity(entity)
+                .insert(Flick::new(Duration::from_millis(120)));
+        }
+    }
+}
+
+fn collect_system(
+    settings: Res<PowerUpSettings>,
+    mut hits: EventReader<HitEvent<PowerUp, Ship>>,
+    mut lives: ResMut<Lives>,
+    mut commands: Commands,
+    pickups: Query<&PowerUp>,
+    mut ships: Query<(&Player, Option<&mut PowerUps>), With<Ship>>,
+) {
+    let mut collected = Vec::new();
+
+    for hit in hits.iter() {
+        let pickup = hit.hittable();
+        if collected.contains(&pickup) {
+            continue;
+        }
+        let (Ok(PowerUp(kind)), Ok((player, power_ups))) =
+            (pickups.get(pickup), ships.get_mut(hit.hurtable()))
+        else {
+            continue;
+        };
+
+        collected.push(pickup);
+        commands.entity(pickup).despawn();
+
+        if *kind == PowerUpKind::ExtraLife {
+            lives.gain(*player);
+            continue;
+        }
+
+        match power_ups {
+            Some(mut power_ups) => power_ups.grant(*kind, settings.duration),
+            None => {
+                let mut power_ups = PowerUps::default();
+                power_ups.grant(*kind, settings.duration);
+                commands.entity(hit.hurtable()).insert(power_ups);
+            }
+        }
+    }
+}
+
+fn power_up_system(
+    fixed_time: Res<FixedTime>,
+    settings: Res<PowerUpSettings>,
+    mut query: Query<(&mut PowerUps, Option<&mut Weapon>, Option<&mut Shield>)>,
+) {
+    for (mut power_ups, weapon, shield) in query.iter_mut() {
+        for (_, timer) in power_ups.active.iter_mut() {
+            timer.tick(fixed_time.period);
+        }
+        power_ups.active.retain(|(_, timer)| !timer.finished());
+
+        if let Some(mut weapon) = weapon {
+            let cooldown = if power_ups.has(PowerUpKind::RapidFire) {
+                weapon.rate_of_fire / settings.rapid_fire_factor
+            } else {
+                weapon.rate_of_fire
+            };
+            if weapon.cooldown.duration() != cooldown {
+                weapon.cooldown.set_duration(cooldown);
+            }
+        }
+
+        if let Some(mut shield) = shield {
+            if power_ups.has(PowerUpKind::Shield) {
+                shield.sustain();
+            } else {
+                shield.release();
+            }
+        }
+    }
+}
+
+fn power_up_hud_setup_system(mut commands: Commands) {
+    let style = |color| TextStyle {
+        font_size: 16.0,
+        color,
+        ..Default::default()
+    };
+
+    for player in (0..MAX_PLAYERS).map(Player) {
+        // The player label comes first, followed by a section for every kind.
+        let sections = std::iter::once(TextSection::new("", style(player.color()))).chain(
+            PowerUpKind::ALL
+                .iter()
+                .map(|kind| TextSection::new("", style(kind.color()))),
+        );
+
+        commands.spawn((
+            TextBundle::from_sections(sections).with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(88.0 + *player as f32 * 18.0),
+                left: Val::Px(16.0),
+                ..Default::default()
+            }),
+            PowerUpText(player),
+            StateVisible(GameState::Playing),
+        ));
+    }
+}
+
+fn power_up_hud_system(
+    count: Res<PlayerCount>,
+    ships: Query<(&Player, &PowerUps)>,
+    mut query: Query<(&mut Text, &PowerUpText)>,
+) {
+    for (mut text, PowerUpText(player)) in query.iter_mut() {
+        let power_ups = ships
+            .iter()
+            .find(|(owner, _)| *owner == player)
+            .map(|(_, power_ups)| power_ups)
+            .filter(|power_ups| !power_ups.active.is_empty());
+
+        let label = match power_ups {
+            Some(_) if **count > 1 => format!("{} ", player.label()),
+            _ => String::new(),
+        };
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+
+        for (section, kind) in text.sections.iter_mut().skip(1).zip(PowerUpKind::ALL) {
+            let remaining = power_ups.and_then(|power_ups| {
+                power_ups
+                    .active
+                    .iter()
+                    .find(|(active, _)| *active == kind)
+                    .map(|(_, timer)| timer.remaining().as_secs_f32().ceil())
+            });
+            let value = match remaining {
+                Some(seconds) => format!("{} {seconds}  ", kind.label()),
+                None => String::new(),
+            };
+            if section.value != value {
+                section.value = value;
+            }
+        }
+    }
+}
@@ -1,22 +1,126 @@
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
 use bevy::prelude::*;
-use rand::{prelude::SmallRng, SeedableRng};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Uses an already inserted [`RandomSeed`] over any configured one, which lets a replay impose the
+/// seed it was recorded with.
+#[derive(Default)]
+pub struct RandomPlugin {
+    seed: Option<u64>,
+}
+
+impl RandomPlugin {
+    pub fn with_seed(seed: u64) -> Self {
+        Self { seed: Some(seed) }
+    }
+
+    /// Takes the seed from a `--seed <n>` argument or the `BEVYROIDS_SEED` variable, falling back
+    /// to entropy when neither is given.
+    pub fn from_env() -> Self {
+        let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+        let seed = args
+            .next()
+            .or_else(|| std::env::var("BEVYROIDS_SEED").ok())
+            .and_then(|seed| seed.parse().ok());
 
-pub struct RandomPlugin;
+        match seed {
+            Some(seed) => Self::with_seed(seed),
+            None => Self::default(),
+        }
+    }
+}
 
 impl Plugin for RandomPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Random(SmallRng::from_entropy()));
+        let seed = app
+            .world
+            .get_resource::<RandomSeed>()
+            .map(|seed| seed.0)
+            .or(self.seed)
+            .unwrap_or_else(rand::random);
+        info!("Random seed: {seed}");
+        app.insert_resource(RandomSeed(seed))
+            .insert_resource(Random(ChaCha8Rng::seed_from_u64(seed)));
     }
 }
 
+#[derive(Debug, Clone, Copy, Deref, Resource)]
+pub struct RandomSeed(pub u64);
+
+/// Generator of a named algorithm, unlike `SmallRng`, so that a seed yields the same numbers on
+/// every target and across dependency updates.
 #[derive(Debug, Deref, DerefMut, Resource)]
-pub struct Random(SmallRng);
+pub struct Random(ChaCha8Rng);
 
 impl FromWorld for Random {
     fn from_world(world: &mut World) -> Self {
         let rng = world
             .get_resource_mut::<Random>()
             .expect("Random resource not found");
-        Random(SmallRng::from_rng(rng.clone()).unwrap())
+        Random(ChaCha8Rng::from_rng(rng.clone()).unwrap())
+    }
+}
+
+/// Names a [`RandomStream`]. The label, not the type, is what the stream is derived from, so it
+/// must be unique and should not change between versions.
+pub trait StreamLabel: Send + Sync + 'static {
+    const LABEL: &'static str;
+}
+
+/// Generator derived only from the [`RandomSeed`] and its label, so it yields the same sequence
+/// for a given seed no matter the order systems are initialized in.
+#[derive(Debug)]
+pub struct RandomStream<L: StreamLabel> {
+    rng: ChaCha8Rng,
+    _label: PhantomData<L>,
+}
+
+impl<L: StreamLabel> RandomStream<L> {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(splitmix64(seed ^ fnv1a(L::LABEL))),
+            _label: PhantomData,
+        }
     }
 }
+
+impl<L: StreamLabel> FromWorld for RandomStream<L> {
+    fn from_world(world: &mut World) -> Self {
+        let seed = world
+            .get_resource::<RandomSeed>()
+            .expect("RandomSeed resource not found");
+        Self::new(**seed)
+    }
+}
+
+impl<L: StreamLabel> Deref for RandomStream<L> {
+    type Target = ChaCha8Rng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+
+impl<L: StreamLabel> DerefMut for RandomStream<L> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
+
+fn fnv1a(label: &str) -> u64 {
+    label.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+
+use crate::{
+    controls::{Action, Binding, BindingStorage, Bindings, PlayerBindings, Source},
+    players::{Player, MAX_PLAYERS},
+    spawn_screen,
+    state::GameState,
+};
+
+/// Menu screen listing the bindings of every player and letting them be changed.
+pub struct RebindingPlugin;
+
+impl Plugin for RebindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Rebinding), rebinding_setup_system)
+            .add_systems(OnExit(GameState::Rebinding), rebinding_save_system)
+            .add_systems(
+                Update,
+                (rebinding_system, rebinding_display_system)
+                    .chain()
+                    .run_if(in_state(GameState::Rebinding)),
+            );
+    }
+}
+
+/// Rows of the screen, by action and the direction it is pushed in.
+const ROWS: [(&str, Action, f32); 7] = [
+    ("ROTATE LEFT", Action::Rotate, 1.0),
+    ("ROTATE RIGHT", Action::Rotate, -1.0),
+    ("THRUST", Action::Thrust, 1.0),
+    ("FIRE", Action::Fire, 1.0),
+    ("HYPERSPACE", Action::Hyperspace, 1.0),
+    ("SHIELD", Action::Shield, 1.0),
+    ("PAUSE", Action::Pause, 1.0),
+];
+
+#[derive(Debug, Default, Resource)]
+struct RebindingMenu {
+    player: usize,
+    row: usize,
+    /// Waiting for the key or button to bind to the selected row.
+    capturing: bool,
+}
+
+#[derive(Debug, Component)]
+struct PlayerText;
+
+#[derive(Debug, Component)]
+struct SchemeText;
+
+#[derive(Debug, Component)]
+struct RowText(usize);
+
+fn matches_row(binding: &Binding, row: usize) -> bool {
+    let (_, action, direction) = ROWS[row];
+    binding.action == action && binding.scale.signum() == direction
+}
+
+/// Binds the source to the row in place of any source of the same kind, and frees it from
+/// wherever else it was bound.
+fn rebind(bindings: &mut PlayerBindings, player: usize, row: usize, source: Source) {
+    let same_kind =
+        |other: &Source| std::mem::discriminant(other) == std::mem::discriminant(&source);
+
+    for (other_player, other) in bindings.iter_mut().enumerate() {
+        other.bindings.retain(|binding| {
+            let replaced =
+                other_player == player && matches_row(binding, row) && same_kind(&binding.source);
+            // Gamepads are not shared, so a button is only taken within the same player.
+            let taken = binding.source == source
+                && (other_player == player || !matches!(source, Source::Button(_)));
+            !replaced && !taken
+        });
+    }
+
+    let (_, action, direction) = ROWS[row];
+    bindings[player].bindings.push(Binding {
+        source,
+        action,
+        scale: direction,
+    });
+}
+
+fn rebinding_setup_system(mut commands: Commands) {
+    commands.insert_resource(RebindingMenu::default());
+
+    let screen = spawn_screen(&mut commands, GameState::Rebinding, &[("CONTROLS", 48.0)]);
+
+    let text_style = |color| TextStyle {
+        font_size: 20.0,
+        color,
+        ..Default::default()
+    };
+
+    commands.entity(screen).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", text_style(Color::WHITE)),
+            PlayerText,
+        ));
+        parent.spawn((
+            TextBundle::from_section("", text_style(Color::GRAY)),
+            SchemeText,
+        ));
+        for row in 0..ROWS.len() {
+            parent.spawn((
+                TextBundle::from_section("", text_style(Color::GRAY)),
+                RowText(row),
+            ));
+        }
+        parent.spawn(TextBundle::from_section(
+            "Up/Down to select, Left/Right to change player, Enter to rebind",
+            text_style(Color::GRAY),
+        ));
+        parent.spawn(TextBundle::from_section(
+            "Tab to change scheme, Backspace to reset the player, Escape to go back",
+            text_style(Color::GRAY),
+        ));
+    });
+}
+
+fn rebinding_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut menu: ResMut<RebindingMenu>,
+    mut bindings: ResMut<PlayerBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if menu.capturing {
+        let source = keyboard_input
+            .get_just_pressed()
+            .next()
+            .map(|key| Source::Key(*key))
+            .or_else(|| {
+                gamepad_input
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Source::Button(button.button_type))
+            })
+            .or_else(|| {
+                mouse_input
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Source::Mouse(*button))
+            });
+
+        match source {
+            Some(Source::Key(KeyCode::Escape)) => menu.capturing = false,
+            Some(source) => {
+                let (player, row) = (menu.player, menu.row);
+                rebind(&mut bindings, player, row, source);
+                menu.capturing = false;
+            }
+            None => {}
+        }
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        menu.row = (menu.row + ROWS.len() - 1) % ROWS.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        menu.row = (menu.row + 1) % ROWS.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::Left) {
+        menu.player = (menu.player + MAX_PLAYERS - 1) % MAX_PLAYERS;
+    }
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        menu.player = (menu.player + 1) % MAX_PLAYERS;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        menu.capturing = true;
+    } else if keyboard_input.just_pressed(KeyCode::Tab) {
+        let scheme = &mut bindings[menu.player].scheme;
+        *scheme = scheme.toggled();
+    } else if keyboard_input.just_pressed(KeyCode::Back) {
+        bindings[menu.player] = Bindings::default_for(Player(menu.player));
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn rebinding_display_system(
+    menu: Res<RebindingMenu>,
+    bindings: Res<PlayerBindings>,
+    mut players: Query<&mut Text, With<PlayerText>>,
+    mut schemes: Query<&mut Text, (With<SchemeText>, Without<PlayerText>)>,
+    mut rows: Query<(&mut Text, &RowText), (Without<PlayerText>, Without<SchemeText>)>,
+) {
+    if !menu.is_changed() && !bindings.is_changed() {
+        return;
+    }
+
+    let player = Player(menu.player);
+
+    for mut text in players.iter_mut() {
+        text.sections[0].value = format!("< {} >", player.label());
+        text.sections[0].style.color = player.color();
+    }
+
+    for mut text in schemes.iter_mut() {
+        let scheme = bindings[*player].scheme;
+        text.sections[0].value = format!("{:<12} {}", "SCHEME", scheme.name());
+    }
+
+    for (mut text, RowText(row)) in rows.iter_mut() {
+        let (label, ..) = ROWS[*row];
+        let selected = *row == menu.row;
+
+        let sources = if selected && menu.capturing {
+            "press a key or button...".to_string()
+        } else {
+            bindings[*player]
+                .bindings
+                .iter()
+                .filter(|binding| matches_row(binding, *row))
+                .filter(|binding| !matches!(binding.source, Source::Touch(_)))
+                .map(|binding| binding.source.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        text.sections[0].value = format!("{label:<12} {sources}");
+        text.sections[0].style.color = if selected { Color::WHITE } else { Color::GRAY };
+    }
+}
+
+fn rebinding_save_system(bindings: Res<PlayerBindings>, storage: Res<BindingStorage>) {
+    bindings.save(storage.0.as_ref());
+}
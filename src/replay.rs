@@ -0,0 +1,343 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    path::PathBuf,
+};
+
+use bevy::{app::AppExit, ecs::schedule::ScheduleLabel, prelude::*};
+
+use crate::{
+    control_source::{ControlSourceLabel, ShipCommands},
+    controls::{ControlFrame, ControlsLabel},
+    players::{CpuPlayers, Player, PlayerCount, MAX_PLAYERS},
+    random::RandomSeed,
+    state::GameState,
+    waves::GameMode,
+};
+
+const MAGIC: &[u8; 4] = b"BVRP";
+const VERSION: u16 = 4;
+
+/// Records the [`ShipCommands`] of every player's ship each fixed tick to a file, or feeds them
+/// back from one.
+///
+/// A replay file is a header made of the `BVRP` magic, a little endian `u16` version, the `u64`
+/// random seed and the [`GameSettings`], followed by three bytes per player and fixed tick: the
+/// control bits, the throttle and the steering of a `ControlFrame`. Version 3 files, from before
+/// the settings were stored, leave them out. Version 2 files, from before analog controls, also
+/// hold only the control bits, and version 1 files, from before local multiplayer, hold them for
+/// the first player alone.
+/// The seed of a replay being played back is inserted as the [`RandomSeed`], so this plugin must
+/// be added before the `RandomPlugin`. Its settings replace the ones picked in the menu.
+pub enum ReplayPlugin {
+    Off,
+    Record(PathBuf),
+    Playback(PathBuf),
+}
+
+impl ReplayPlugin {
+    /// Reads the mode from a `--record <path>` or `--replay <path>` argument.
+    pub fn from_env() -> Self {
+        let args = std::env::args().collect::<Vec<_>>();
+        let value = |name: &str| {
+            args.iter()
+                .skip_while(|arg| *arg != name)
+                .nth(1)
+                .map(PathBuf::from)
+        };
+
+        if let Some(path) = value("--replay") {
+            Self::Playback(path)
+        } else if let Some(path) = value("--record") {
+            Self::Record(path)
+        } else {
+            Self::Off
+        }
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match self {
+            Self::Off => {}
+
+            Self::Record(path) => {
+                app.insert_resource(RecordingPath(path.clone()))
+                    .add_systems(Startup, recording_start_system)
+                    .add_systems(
+                        FixedUpdate,
+                        recording_system
+                            .in_set(ReplaySystemLabel)
+                            .after(ControlSourceLabel),
+                    )
+                    .add_systems(Last, recording_flush_system);
+            }
+
+            Self::Playback(path) => match Replay::load(path) {
+                Ok(replay) => {
+                    info!("Playing replay {}", path.display());
+                    if replay.settings.is_none() {
+                        warn!(
+                            "Replay {} predates stored game settings and the current random \
+                             generator, it will not play back the same",
+                            path.display()
+                        );
+                    }
+                    app.insert_resource(RandomSeed(replay.seed))
+                        .insert_resource(replay)
+                        .add_systems(Startup, playback_settings_system)
+                        .add_systems(OnExit(GameState::MainMenu), playback_settings_system)
+                        .add_systems(FixedUpdate, playback_system.in_set(ControlsLabel));
+                }
+                Err(err) => error!("Could not load replay {}: {err}", path.display()),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct ReplaySystemLabel;
+
+/// Menu choices a game depends on, which a replay has to be played back with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GameSettings {
+    players: PlayerCount,
+    mode: GameMode,
+    cpu_players: CpuPlayers,
+}
+
+impl GameSettings {
+    const SIZE: usize = 3;
+
+    fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mode = match self.mode {
+            GameMode::Waves => 0,
+            GameMode::Endless => 1,
+            GameMode::Versus => 2,
+        };
+        [*self.players as u8, mode, u8::from(*self.cpu_players)]
+    }
+
+    fn from_bytes(bytes: [u8; Self::SIZE]) -> io::Result<Self> {
+        let mode = match bytes[1] {
+            0 => GameMode::Waves,
+            1 => GameMode::Endless,
+            2 => GameMode::Versus,
+            mode => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown game mode {mode}"),
+                ))
+            }
+        };
+
+        if !(1..=MAX_PLAYERS).contains(&usize::from(bytes[0])) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported player count {}", bytes[0]),
+            ));
+        }
+
+        Ok(Self {
+            players: PlayerCount::new(bytes[0].into()),
+            mode,
+            cpu_players: CpuPlayers(bytes[2] != 0),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct Replay {
+    seed: u64,
+    /// Settings the replay was recorded with, missing from older files.
+    settings: Option<GameSettings>,
+    ticks: Vec<u8>,
+    /// Streams stored for every tick.
+    players: usize,
+    /// Whether the streams hold full control frames or control bits alone.
+    analog: bool,
+    cursor: usize,
+    /// Controls of every stream for the current tick.
+    current: [ControlFrame; MAX_PLAYERS],
+}
+
+impl Replay {
+    pub fn load(path: &PathBuf) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+
+        if bytes.len() < 14 || &bytes[0..4] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a replay file",
+            ));
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        let (players, analog) = match version {
+            1 => (1, false),
+            2 => (MAX_PLAYERS, false),
+            3 | VERSION => (MAX_PLAYERS, true),
+            version => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsupported replay version {version}"),
+                ))
+            }
+        };
+
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&bytes[6..14]);
+
+        let (settings, header) = if version == VERSION {
+            let mut settings = [0; GameSettings::SIZE];
+            settings.copy_from_slice(bytes.get(14..14 + GameSettings::SIZE).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "truncated replay header")
+            })?);
+            (
+                Some(GameSettings::from_bytes(settings)?),
+                14 + GameSettings::SIZE,
+            )
+        } else {
+            (None, 14)
+        };
+
+        Ok(Self {
+            seed: u64::from_le_bytes(seed),
+            settings,
+            ticks: bytes.split_off(header),
+            players,
+            analog,
+            cursor: 0,
+            current: [ControlFrame::default(); MAX_PLAYERS],
+        })
+    }
+
+    /// Controls recorded for the stream of a player on the current tick.
+    pub fn frame(&self, stream: usize) -> ControlFrame {
+        self.current.get(stream).copied().unwrap_or_default()
+    }
+
+    fn frame_size(&self) -> usize {
+        if self.analog {
+            3
+        } else {
+            1
+        }
+    }
+}
+
+#[derive(Debug, Resource)]
+struct RecordingPath(PathBuf);
+
+/// The file being recorded to, along with the settings of its first game once it has started,
+/// which the header is only written with then.
+#[derive(Debug, Resource)]
+struct Recording(BufWriter<File>, Option<GameSettings>);
+
+fn recording_start_system(mut commands: Commands, path: Res<RecordingPath>) {
+    match File::create(&path.0) {
+        Ok(file) => {
+            info!("Recording replay to {}", path.0.display());
+            commands.insert_resource(Recording(BufWriter::new(file), None));
+        }
+        Err(err) => error!("Could not record replay to {}: {err}", path.0.display()),
+    }
+}
+
+fn recording_system(
+    mut commands: Commands,
+    seed: Res<RandomSeed>,
+    players: Res<PlayerCount>,
+    mode: Res<GameMode>,
+    cpu_players: Res<CpuPlayers>,
+    ships: Query<(&Player, &ShipCommands)>,
+    recording: Option<ResMut<Recording>>,
+) {
+    let Some(mut recording) = recording else {
+        return;
+    };
+
+    let settings = GameSettings {
+        players: *players,
+        mode: *mode,
+        cpu_players: *cpu_players,
+    };
+
+    let mut bytes = Vec::new();
+    match recording.1 {
+        None => {
+            bytes.extend(MAGIC);
+            bytes.extend(VERSION.to_le_bytes());
+            bytes.extend(seed.to_le_bytes());
+            bytes.extend(settings.to_bytes());
+            recording.1 = Some(settings);
+        }
+        Some(recorded) if recorded != settings => {
+            error!("Replay recording stopped: a game was started with other settings");
+            commands.remove_resource::<Recording>();
+            return;
+        }
+        Some(_) => {}
+    }
+
+    let mut frames = [ControlFrame::default(); MAX_PLAYERS];
+    for (player, commands) in ships.iter() {
+        frames[**player] = commands.frame();
+    }
+    bytes.extend(frames.iter().flat_map(|frame| frame.to_bytes()));
+
+    if let Err(err) = recording.0.write_all(&bytes) {
+        error!("Replay recording stopped: {err}");
+        commands.remove_resource::<Recording>();
+    }
+}
+
+/// The window closing ends the process without dropping the world, so the buffered tail of the
+/// recording has to be written out on exit.
+fn recording_flush_system(mut exits: EventReader<AppExit>, recording: Option<ResMut<Recording>>) {
+    if exits.iter().next().is_none() {
+        return;
+    }
+
+    if let Some(mut recording) = recording {
+        if let Err(err) = recording.0.flush() {
+            error!("Could not finish replay recording: {err}");
+        }
+    }
+}
+
+/// Puts back the settings the replay was recorded with, over anything picked in the menu.
+fn playback_settings_system(
+    replay: Res<Replay>,
+    mut players: ResMut<PlayerCount>,
+    mut mode: ResMut<GameMode>,
+    mut cpu_players: ResMut<CpuPlayers>,
+) {
+    if let Some(settings) = replay.settings {
+        *players = settings.players;
+        *mode = settings.mode;
+        *cpu_players = settings.cpu_players;
+    }
+}
+
+fn playback_system(mut replay: ResMut<Replay>) {
+    let size = replay.frame_size();
+    let start = replay.cursor * replay.players * size;
+    let mut frames = [ControlFrame::default(); MAX_PLAYERS];
+    if let Some(tick) = replay.ticks.get(start..start + replay.players * size) {
+        for (frame, bytes) in frames.iter_mut().zip(tick.chunks_exact(size)) {
+            *frame = match *bytes {
+                [bits, throttle, steering] => ControlFrame::from_bytes([bits, throttle, steering]),
+                _ => ControlFrame::from_bits(bytes[0]),
+            };
+        }
+    }
+
+    if start == replay.ticks.len() {
+        info!("Replay finished");
+    }
+
+    replay.cursor += 1;
+    replay.current = frames;
+}
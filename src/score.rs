@@ -0,0 +1,97 @@
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+
+use crate::{
+    players::{Player, PlayerCount, MAX_PLAYERS},
+    state::{GameState, StateVisible},
+};
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .add_event::<ScoreEvent>()
+            .add_systems(Startup, score_hud_setup_system)
+            .add_systems(FixedUpdate, score_system.in_set(ScoreSystemLabel))
+            .add_systems(PostUpdate, score_hud_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct ScoreSystemLabel;
+
+/// Points of every player.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+pub struct Score([u32; MAX_PLAYERS]);
+
+impl Score {
+    pub fn get(&self, player: Player) -> u32 {
+        self.0[*player]
+    }
+
+    /// Highest score of any player, standing for how far the game as a whole has gone.
+    pub fn best(&self) -> u32 {
+        self.0.into_iter().max().unwrap_or_default()
+    }
+
+    pub fn reset(&mut self) {
+        self.0 = [0; MAX_PLAYERS];
+    }
+}
+
+/// Points awarded to a player by gameplay, added to the [`Score`] later in the same fixed tick.
+#[derive(Debug, Event)]
+pub struct ScoreEvent(pub Player, pub u32);
+
+#[derive(Debug, Component)]
+struct ScoreText;
+
+fn score_system(mut events: EventReader<ScoreEvent>, mut score: ResMut<Score>) {
+    for ScoreEvent(player, points) in events.iter() {
+        score.0[**player] += points;
+    }
+}
+
+fn score_hud_setup_system(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_sections((0..MAX_PLAYERS).map(|player| {
+            TextSection::new(
+                "",
+                TextStyle {
+                    font_size: 32.0,
+                    color: Player(player).color(),
+                    ..Default::default()
+                },
+            )
+        }))
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(16.0),
+            ..Default::default()
+        }),
+        ScoreText,
+        StateVisible(GameState::Playing),
+    ));
+}
+
+fn score_hud_system(
+    score: Res<Score>,
+    count: Res<PlayerCount>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    if !score.is_changed() && !count.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        for (player, section) in text.sections.iter_mut().enumerate() {
+            let player = Player(player);
+            section.value = match **count {
+                1 if *player == 0 => score.get(player).to_string(),
+                count if *player < count => format!("{} {}  ", player.label(), score.get(player)),
+                _ => String::new(),
+            };
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+
+use crate::{
+    boundary::Bounding,
+    collision::{CollisionSystemLabel, HitEvent},
+    physics::Velocity,
+    players::{Player, PlayerCount, MAX_PLAYERS},
+    state::{GameState, StateScoped, StateVisible},
+    Asteroid, Bullet, Ship,
+};
+
+pub struct ShieldPlugin;
+
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, shield_meter_setup_system)
+            .add_systems(
+                FixedUpdate,
+                (
+                    shield_system.before(CollisionSystemLabel),
+                    shield_hit_system.after(CollisionSystemLabel),
+                )
+                    .in_set(ShieldSystemLabel),
+            )
+            .add_systems(Update, shield_ring_system)
+            .add_systems(PostUpdate, shield_meter_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct ShieldSystemLabel;
+
+/// Protects a ship from asteroids and bullets while active, at the cost of its charge.
+#[derive(Debug, Component)]
+pub struct Shield {
+    active: bool,
+    /// Whether the shield was raised by [`Shield::sustain`] rather than by the player.
+    sustained: bool,
+    /// Remaining charge, from `0.0` to `1.0`.
+    charge: f32,
+    /// Charge used per second while active.
+    drain: f32,
+    /// Charge regained per second while inactive.
+    recharge: f32,
+    /// Charge used by every deflected hit.
+    hit_cost: f32,
+    /// Charge needed before the shield can be raised again.
+    threshold: f32,
+}
+
+impl Default for Shield {
+    fn default() -> Self {
+        Self {
+            active: false,
+            sustained: false,
+            charge: 1.0,
+            drain: 0.4,
+            recharge: 0.1,
+            hit_cost: 0.15,
+            threshold: 0.2,
+        }
+    }
+}
+
+impl Shield {
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    pub fn toggle(&mut self) {
+        self.active = !self.active && self.charge >= self.threshold;
+        self.sustained = false;
+    }
+
+    pub fn lower(&mut self) {
+        self.active = false;
+        self.sustained = false;
+    }
+
+    /// Raises the shield and refills it, for effects that keep it up regardless of charge.
+    pub fn sustain(&mut self) {
+        self.sustained |= !self.active;
+        self.active = true;
+        self.charge = 1.0;
+    }
+
+    /// Ends the effect of [`Shield::sustain`], lowering the shield unless the player raised it.
+    pub fn release(&mut self) {
+        if self.sustained {
+            self.lower();
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+struct ShieldRing(Entity);
+
+/// Frame of the shield meter of a player, shown side by side with the others.
+#[derive(Debug, Component)]
+struct ShieldMeter(Player);
+
+#[derive(Debug, Component)]
+struct ShieldCharge(Player);
+
+const METER_WIDTH: f32 = 80.0;
+
+fn shield_system(fixed_time: Res<FixedTime>, mut query: Query<&mut Shield>) {
+    let delta = fixed_time.period.as_secs_f32();
+
+    for mut shield in query.iter_mut() {
+        if shield.active {
+            shield.charge = (shield.charge - shield.drain * delta).max(0.0);
+            if shield.charge == 0.0 {
+                shield.active = false;
+            }
+        } else if shield.charge < 1.0 {
+            shield.charge = (shield.charge + shield.recharge * delta).min(1.0);
+        }
+    }
+}
+
+fn shield_hit_system(
+    mut commands: Commands,
+    mut asteroid_hits: EventReader<HitEvent<Asteroid, Ship>>,
+    mut bullet_hits: EventReader<HitEvent<Bullet, Ship>>,
+    mut ships: Query<(&mut Shield, &mut Transform, &mut Velocity, &Bounding), With<Ship>>,
+    asteroids: Query<(&Transform, &Bounding), (With<Asteroid>, Without<Ship>)>,
+) {
+    for hit in asteroid_hits.iter() {
+        let Ok((mut shield, mut transform, mut velocity, bounds)) = ships.get_mut(hit.hurtable())
+        else {
+            continue;
+        };
+        let Ok((asteroid, asteroid_bounds)) = asteroids.get(hit.hittable()) else {
+            continue;
+        };
+        if !shield.active {
+            continue;
+        }
+
+        shield.charge = (shield.charge - shield.hit_cost).max(0.0);
+
+        let normal = (transform.translation - asteroid.translation)
+            .truncate()
+            .try_normalize()
+            .unwrap_or(Vec2::X);
+
+        // Push the ship clear of the asteroid so the same contact is not reported again.
+        let contact = asteroid.translation.truncate() + normal * (**bounds + **asteroid_bounds);
+        transform.translation.x = contact.x;
+        transform.translation.y = contact.y;
+
+        let approach = velocity.dot(normal);
+        if approach < 0.0 {
+            **velocity -= 2.0 * approach * normal;
+        }
+    }
+
+    for hit in bullet_hits.iter() {
+        let Ok((mut shield, ..)) = ships.get_mut(hit.hurtable()) else {
+            continue;
+        };
+        if !shield.active {
+            continue;
+        }
+
+        shield.charge = (shield.charge - shield.hit_cost).max(0.0);
+        if let Some(mut bullet) = commands.get_entity(hit.hittable()) {
+            bullet.despawn();
+        }
+    }
+}
+
+fn shield_ring_system(
+    mut commands: Commands,
+    added: Query<(Entity, &Bounding), Added<Shield>>,
+    shields: Query<(&Shield, &Transform)>,
+    mut rings: Query<
+        (
+            Entity,
+            &ShieldRing,
+            &mut Transform,
+            &mut Visibility,
+            &mut Stroke,
+        ),
+        Without<Shield>,
+    >,
+) {
+    for (entity, bounds) in added.iter() {
+        commands.spawn((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shapes::Circle {
+                    radius: **bounds + 6.0,
+                    center: Vec2::ZERO,
+                }),
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            Stroke::new(Color::rgb(0.3, 0.7, 1.0), 1.5),
+            ShieldRing(entity),
+            StateScoped(GameState::Playing),
+        ));
+    }
+
+    for (ring, ShieldRing(owner), mut transform, mut visibility, mut stroke) in rings.iter_mut() {
+        let Ok((shield, owner_transform)) = shields.get(*owner) else {
+            commands.entity(ring).despawn();
+            continue;
+        };
+
+        transform.translation = owner_transform.translation;
+        *visibility = if shield.active {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        stroke.color.set_a(0.3 + 0.7 * shield.charge);
+    }
+}
+
+fn shield_meter_setup_system(mut commands: Commands) {
+    for player in (0..MAX_PLAYERS).map(Player) {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(74.0),
+                        left: Val::Px(16.0 + *player as f32 * (METER_WIDTH + 12.0)),
+                        width: Val::Px(METER_WIDTH),
+                        height: Val::Px(6.0),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..Default::default()
+                    },
+                    border_color: player.color().into(),
+                    ..Default::default()
+                },
+                ShieldMeter(player),
+                StateVisible(GameState::Playing),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(0.0),
+                            height: Val::Percent(100.0),
+                            ..Default::default()
+                        },
+                        background_color: Color::rgb(0.3, 0.7, 1.0).into(),
+                        ..Default::default()
+                    },
+                    ShieldCharge(player),
+                ));
+            });
+    }
+}
+
+fn shield_meter_system(
+    count: Res<PlayerCount>,
+    shields: Query<(&Player, &Shield)>,
+    mut meters: Query<(&mut Style, &ShieldMeter), Without<ShieldCharge>>,
+    mut charges: Query<(&mut Style, &ShieldCharge), Without<ShieldMeter>>,
+) {
+    for (mut style, ShieldMeter(player)) in meters.iter_mut() {
+        let display = if **player < **count {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+
+    for (mut style, ShieldCharge(player)) in charges.iter_mut() {
+        let charge = shields
+            .iter()
+            .find(|(owner, _)| *owner == player)
+            .map_or(0.0, |(_, shield)| shield.charge);
+        let width = Val::Px((METER_WIDTH - 2.0) * charge);
+
+        if style.width != width {
+            style.width = width;
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+
+use crate::clock::GameClock;
+
+pub struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .configure_set(
+                Update,
+                GameplaySystemLabel.run_if(in_state(GameState::Playing)),
+            )
+            .configure_set(
+                FixedUpdate,
+                GameplaySystemLabel.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                state_visibility_system.run_if(state_changed::<GameState>()),
+            )
+            .add_systems(OnEnter(GameState::Paused), pause_system)
+            .add_systems(OnExit(GameState::Paused), resume_system);
+
+        for state in GameState::ALL {
+            app.add_systems(OnExit(state), scope_cleanup_system(state));
+        }
+    }
+}
+
+/// Systems that only run while the game is being played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct GameplaySystemLabel;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    Playing,
+    Paused,
+    HighScoreEntry,
+    GameOver,
+    RoundResults,
+    Rebinding,
+}
+
+impl GameState {
+    const ALL: [GameState; 7] = [
+        GameState::MainMenu,
+        GameState::Playing,
+        GameState::Paused,
+        GameState::HighScoreEntry,
+        GameState::GameOver,
+        GameState::RoundResults,
+        GameState::Rebinding,
+    ];
+
+    /// States layered on top of another one, which keeps its scoped entities alive underneath.
+    fn parent(self) -> Option<GameState> {
+        match self {
+            GameState::Paused => Some(GameState::Playing),
+            _ => None,
+        }
+    }
+
+    fn within(self, other: GameState) -> bool {
+        self == other || self.parent().is_some_and(|parent| parent.within(other))
+    }
+}
+
+/// Despawns the entity once the game leaves the given state for one that is not layered on it.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct StateScoped(pub GameState);
+
+/// Shows the entity only while the game is within the given state, for entities that live on
+/// outside of it, such as the HUD.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct StateVisible(pub GameState);
+
+fn state_visibility_system(
+    state: Res<State<GameState>>,
+    mut query: Query<(&mut Visibility, &StateVisible)>,
+) {
+    for (mut visibility, StateVisible(scope)) in query.iter_mut() {
+        *visibility = if state.get().within(*scope) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn scope_cleanup_system(
+    exited: GameState,
+) -> impl FnMut(Commands, Res<State<GameState>>, Query<(Entity, &StateScoped)>) {
+    move |mut commands, entered, query| {
+        for (entity, StateScoped(scope)) in query.iter() {
+            if exited.within(*scope) && !entered.get().within(*scope) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn pause_system(mut clock: ResMut<GameClock>) {
+    clock.set_menu_paused(true);
+}
+
+fn resume_system(mut clock: ResMut<GameClock>) {
+    clock.set_menu_paused(false);
+}
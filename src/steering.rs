@@ -0,0 +1,234 @@
+use std::f32::consts::PI;
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use rand::Rng;
+
+use crate::{
+    boundary::Bounding,
+    physics::Velocity,
+    random::{RandomStream, StreamLabel},
+};
+
+pub struct SteeringPlugin;
+
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, steering_system.in_set(SteeringSystemLabel));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct SteeringSystemLabel;
+
+/// Something steering entities with [`Behavior::Avoid`] keep clear of.
+#[derive(Debug, Component)]
+pub struct Obstacle;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Behavior {
+    /// Holds the given velocity along its own direction, leaving sideways motion to others.
+    Cruise(Vec2),
+    /// Heads straight for the entity.
+    Seek(Entity),
+    /// Heads for the point, slowing down on arrival.
+    MoveTo(Vec2),
+    /// Runs away from the entity while it is closer than `radius`.
+    Flee { target: Entity, radius: f32 },
+    /// Drifts around by steering toward a point that jitters on a circle ahead.
+    Wander {
+        distance: f32,
+        radius: f32,
+        jitter: f32,
+    },
+    /// Steers around [`Obstacle`]s found up to `look_ahead` seconds ahead.
+    Avoid { look_ahead: f32, margin: f32 },
+    /// Oscillates smoothly along the axis.
+    Sine {
+        axis: Vec2,
+        amplitude: f32,
+        frequency: f32,
+    },
+    /// Alternates between full speed one way and the other along the axis.
+    ZigZag {
+        axis: Vec2,
+        amplitude: f32,
+        frequency: f32,
+    },
+}
+
+/// Blends weighted [`Behavior`]s into a steering force applied to the entity's [`Velocity`].
+#[derive(Debug, Clone, Component)]
+pub struct Steering {
+    behaviors: Vec<(Behavior, f32)>,
+    /// Behavior chosen from outside, for example by an AI, that takes the place of any
+    /// [`Behavior::Cruise`].
+    goal: Option<Behavior>,
+    max_speed: f32,
+    /// Largest change of velocity per second.
+    max_force: f32,
+    elapsed: f32,
+    wander_angle: f32,
+}
+
+impl Steering {
+    pub fn new(max_speed: f32, max_force: f32) -> Self {
+        Self {
+            behaviors: Vec::new(),
+            goal: None,
+            max_speed,
+            max_force,
+            elapsed: 0.0,
+            wander_angle: 0.0,
+        }
+    }
+
+    pub fn with(mut self, behavior: Behavior, weight: f32) -> Self {
+        self.behaviors.push((behavior, weight));
+        self
+    }
+
+    pub fn with_all(mut self, behaviors: impl IntoIterator<Item = (Behavior, f32)>) -> Self {
+        self.behaviors.extend(behaviors);
+        self
+    }
+
+    pub fn max_speed(&self) -> f32 {
+        self.max_speed
+    }
+
+    pub fn set_goal(&mut self, goal: Option<Behavior>) {
+        self.goal = goal;
+    }
+}
+
+struct SteeringWander;
+
+impl StreamLabel for SteeringWander {
+    const LABEL: &'static str = "steering_wander";
+}
+
+fn steering_system(
+    fixed_time: Res<FixedTime>,
+    mut rng: Local<RandomStream<SteeringWander>>,
+    mut query: Query<(
+        Entity,
+        &mut Steering,
+        &mut Velocity,
+        &Transform,
+        Option<&Bounding>,
+    )>,
+    targets: Query<&Transform>,
+    obstacles: Query<(Entity, &Transform, &Bounding), With<Obstacle>>,
+) {
+    let delta = fixed_time.period.as_secs_f32();
+
+    for (entity, mut steering, mut velocity, transform, bounds) in query.iter_mut() {
+        steering.elapsed += delta;
+
+        let position = transform.translation.truncate();
+        let radius = bounds.map_or(0.0, |bounds| **bounds);
+        let max_speed = steering.max_speed;
+        let heading = velocity.try_normalize().unwrap_or(Vec2::X);
+        let toward = |direction: Vec2| direction * max_speed - **velocity;
+
+        let mut force = Vec2::ZERO;
+
+        let goal = steering.goal;
+        let behaviors = steering
+            .behaviors
+            .iter()
+            .copied()
+            .filter(|(behavior, _)| goal.is_none() || !matches!(behavior, Behavior::Cruise(_)))
+            .chain(goal.map(|goal| (goal, 1.0)))
+            .collect::<Vec<_>>();
+
+        for (behavior, weight) in behaviors {
+            let behavior_force = match behavior {
+                Behavior::Cruise(cruise) => {
+                    let axis = cruise.normalize_or_zero();
+                    axis * (cruise.length() - velocity.dot(axis))
+                }
+
+                Behavior::Seek(target) => match targets.get(target) {
+                    Ok(target) => {
+                        toward((target.translation.truncate() - position).normalize_or_zero())
+                    }
+                    Err(_) => Vec2::ZERO,
+                },
+
+                Behavior::MoveTo(point) => {
+                    let offset = point - position;
+                    let arrival = (offset.length() / 50.0).min(1.0);
+                    toward(offset.normalize_or_zero() * arrival)
+                }
+
+                Behavior::Flee { target, radius } => match targets.get(target) {
+                    Ok(target) if target.translation.truncate().distance(position) < radius => {
+                        toward((position - target.translation.truncate()).normalize_or_zero())
+                    }
+                    _ => Vec2::ZERO,
+                },
+
+                Behavior::Wander {
+                    distance,
+                    radius,
+                    jitter,
+                } => {
+                    steering.wander_angle += rng.gen_range(-jitter..jitter) * delta;
+                    let point =
+                        heading * distance + Vec2::from_angle(steering.wander_angle) * radius;
+                    toward(point.normalize_or_zero())
+                }
+
+                Behavior::Avoid { look_ahead, margin } => {
+                    let ahead = **velocity * look_ahead;
+
+                    obstacles
+                        .iter()
+                        .filter(|(obstacle, ..)| *obstacle != entity)
+                        .filter_map(|(_, obstacle, obstacle_bounds)| {
+                            let center = obstacle.translation.truncate();
+                            let along = (center - position).dot(heading).clamp(0.0, ahead.length());
+                            let closest = position + heading * along;
+                            let clearance = radius + **obstacle_bounds + margin;
+                            let distance = closest.distance(center);
+                            (distance < clearance).then_some((along, closest - center, clearance))
+                        })
+                        .min_by(|(a, ..), (b, ..)| a.total_cmp(b))
+                        .map_or(Vec2::ZERO, |(_, offset, clearance)| {
+                            // Push harder the deeper the path cuts into the obstacle.
+                            let strength = (1.0 - offset.length() / clearance).max(0.5);
+                            let away = offset.try_normalize().unwrap_or(heading.perp());
+                            away * steering.max_force * strength
+                        })
+                }
+
+                Behavior::Sine {
+                    axis,
+                    amplitude,
+                    frequency,
+                } => {
+                    let axis = axis.normalize_or_zero();
+                    let target = amplitude * (2.0 * PI * frequency * steering.elapsed).sin();
+                    axis * (target - velocity.dot(axis))
+                }
+
+                Behavior::ZigZag {
+                    axis,
+                    amplitude,
+                    frequency,
+                } => {
+                    let axis = axis.normalize_or_zero();
+                    let phase = (frequency * steering.elapsed).fract();
+                    let target = if phase < 0.5 { amplitude } else { -amplitude };
+                    axis * (target - velocity.dot(axis))
+                }
+            };
+
+            force += behavior_force * weight;
+        }
+
+        let force = force.clamp_length_max(steering.max_force);
+        **velocity = (**velocity + force * delta).clamp_length_max(max_speed);
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(any(test, not(target_arch = "wasm32")))]
+use std::sync::Mutex;
+use std::{io, sync::Arc};
+
+use bevy::prelude::*;
+
+/// Where a serialized document, like the high score table or the input bindings, is kept.
+pub trait Storage: Send + Sync + 'static {
+    /// Returns `None` when nothing has been saved yet.
+    fn load(&self) -> io::Result<Option<String>>;
+
+    fn save(&self, contents: &str) -> io::Result<()>;
+}
+
+/// Storage for the named document in the platform config directory, or in browser storage on the
+/// web. Falls back to memory when neither is available.
+pub fn config_storage(name: &str) -> Arc<dyn Storage> {
+    #[cfg(target_arch = "wasm32")]
+    return Arc::new(BrowserStorage::new(format!("bevyroids.{name}")));
+
+    #[cfg(not(target_arch = "wasm32"))]
+    match FileStorage::in_config_dir(&format!("{name}.ron")) {
+        Some(storage) => Arc::new(storage),
+        None => {
+            warn!("No config directory found, {name} will not be kept");
+            Arc::new(MemoryStorage::default())
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    path: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn in_config_dir(file_name: &str) -> Option<Self> {
+        directories::ProjectDirs::from("", "", "bevyroids")
+            .map(|dirs| Self::new(dirs.config_dir().join(file_name)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn load(&self) -> io::Result<Option<String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Writes to a temporary file first so a crash mid-write cannot truncate the document.
+    fn save(&self, contents: &str) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temporary = self.path.with_extension("tmp");
+        std::fs::write(&temporary, contents)?;
+        std::fs::rename(&temporary, &self.path)
+    }
+}
+
+#[cfg(any(test, not(target_arch = "wasm32")))]
+#[derive(Default)]
+pub struct MemoryStorage {
+    contents: Mutex<Option<String>>,
+}
+
+#[cfg(any(test, not(target_arch = "wasm32")))]
+impl Storage for MemoryStorage {
+    fn load(&self) -> io::Result<Option<String>> {
+        Ok(self.contents.lock().unwrap().clone())
+    }
+
+    fn save(&self, contents: &str) -> io::Result<()> {
+        *self.contents.lock().unwrap() = Some(contents.to_owned());
+        Ok(())
+    }
+}
+
+/// Keeps the document in the window's `localStorage` under the given key.
+#[cfg(target_arch = "wasm32")]
+pub struct BrowserStorage {
+    key: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl BrowserStorage {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self { key: key.into() }
+    }
+
+    fn storage() -> io::Result<web_sys::Storage> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "no local storage"))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for BrowserStorage {
+    fn load(&self) -> io::Result<Option<String>> {
+        Self::storage()?
+            .get_item(&self.key)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "local storage read failed"))
+    }
+
+    fn save(&self, contents: &str) -> io::Result<()> {
+        Self::storage()?
+            .set_item(&self.key, contents)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "local storage write failed"))
+    }
+}
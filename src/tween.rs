@@ -0,0 +1,315 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::{
+    plugin::BuildShapes,
+    prelude::{Fill, Stroke},
+};
+
+use crate::clock::GameClock;
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, tween_system.before(BuildShapes));
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    SineInOut,
+    /// Holds the starting value until the very end of the segment.
+    Step,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => t * (2.0 - t),
+            Ease::SineInOut => 0.5 * (1.0 - (PI * t).cos()),
+            Ease::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Repeat {
+    #[default]
+    Once,
+    Loop,
+    PingPong,
+}
+
+pub trait Lerp {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Vec3::lerp(*self, *other, t)
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let from = Vec4::from(self.as_rgba_f32());
+        let to = Vec4::from(other.as_rgba_f32());
+        let color = from.lerp(to, t);
+        Color::rgba(color.x, color.y, color.z, color.w)
+    }
+}
+
+/// A list of values placed along the normalized `0.0..=1.0` progress of a [`Tween`]. The easing
+/// of each keyframe shapes the segment that ends on it.
+#[derive(Debug, Clone)]
+pub struct Keyframes<T> {
+    frames: Vec<(f32, T, Ease)>,
+}
+
+impl<T: Lerp + Clone> Keyframes<T> {
+    pub fn new(start: T) -> Self {
+        Self {
+            frames: vec![(0.0, start, Ease::Linear)],
+        }
+    }
+
+    pub fn from_to(from: T, to: T, ease: Ease) -> Self {
+        Self::new(from).then(1.0, to, ease)
+    }
+
+    pub fn then(mut self, at: f32, value: T, ease: Ease) -> Self {
+        let at = at.clamp(0.0, 1.0);
+        let index = self.frames.partition_point(|(time, _, _)| *time <= at);
+        self.frames.insert(index, (at, value, ease));
+        self
+    }
+
+    pub fn sample(&self, t: f32) -> T {
+        let next = self.frames.partition_point(|(time, _, _)| *time <= t);
+
+        if next == 0 {
+            return self.frames[0].1.clone();
+        }
+
+        if next == self.frames.len() {
+            return self.frames[next - 1].1.clone();
+        }
+
+        let (from_time, from, _) = &self.frames[next - 1];
+        let (to_time, to, ease) = &self.frames[next];
+        let span = to_time - from_time;
+        let local = if span > 0.0 {
+            (t - from_time) / span
+        } else {
+            1.0
+        };
+
+        from.lerp(to, ease.apply(local))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Track {
+    Scale(Keyframes<Vec3>),
+    /// Rotation around the z axis, in radians.
+    Rotation(Keyframes<f32>),
+    FillColor(Keyframes<Color>),
+    StrokeColor(Keyframes<Color>),
+    /// Alpha of both the fill and the stroke, applied on top of any color track.
+    Alpha(Keyframes<f32>),
+}
+
+/// Keyframed animation of an entity's transform and shape colors, progressing in time rather
+/// than in frames.
+#[derive(Debug, Clone, Component)]
+pub struct Tween {
+    duration: Duration,
+    elapsed: Duration,
+    repeat: Repeat,
+    tracks: Vec<Track>,
+}
+
+impl Tween {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            elapsed: Duration::ZERO,
+            repeat: Repeat::Once,
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Hard blinking, visible for one `frequency` and hidden for the next.
+    pub fn flick(frequency: Duration) -> Self {
+        Self::new(frequency * 2)
+            .with_repeat(Repeat::Loop)
+            .with_alpha(
+                Keyframes::new(1.0)
+                    .then(0.5, 0.0, Ease::Step)
+                    .then(1.0, 0.0, Ease::Step),
+            )
+    }
+
+    /// Uniform scale growth of `rate` per second, held once `duration` is reached.
+    pub fn growth(rate: f32, duration: Duration) -> Self {
+        let scale = 1.0 + rate * duration.as_secs_f32();
+        Self::new(duration).with_scale(Keyframes::from_to(
+            Vec3::ONE,
+            Vec3::splat(scale),
+            Ease::Linear,
+        ))
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn with_track(mut self, track: Track) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    pub fn with_scale(self, keyframes: Keyframes<Vec3>) -> Self {
+        self.with_track(Track::Scale(keyframes))
+    }
+
+    pub fn with_rotation(self, keyframes: Keyframes<f32>) -> Self {
+        self.with_track(Track::Rotation(keyframes))
+    }
+
+    pub fn with_fill(self, keyframes: Keyframes<Color>) -> Self {
+        self.with_track(Track::FillColor(keyframes))
+    }
+
+    pub fn with_stroke(self, keyframes: Keyframes<Color>) -> Self {
+        self.with_track(Track::StrokeColor(keyframes))
+    }
+
+    pub fn with_alpha(self, keyframes: Keyframes<f32>) -> Self {
+        self.with_track(Track::Alpha(keyframes))
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        self.elapsed += delta;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.repeat == Repeat::Once && self.elapsed >= self.duration
+    }
+
+    /// Normalized position within the current cycle, after applying the repeat mode.
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+
+        let cycles = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+
+        match self.repeat {
+            Repeat::Once => cycles.min(1.0),
+            Repeat::Loop => cycles.fract(),
+            Repeat::PingPong => {
+                let t = cycles % 2.0;
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+        }
+    }
+
+    pub fn apply(
+        &self,
+        transform: Option<&mut Transform>,
+        fill: Option<&mut Fill>,
+        stroke: Option<&mut Stroke>,
+    ) {
+        let t = self.progress();
+        let mut transform = transform;
+        let mut fill = fill;
+        let mut stroke = stroke;
+
+        for track in self.tracks.iter() {
+            match track {
+                Track::Scale(keyframes) => {
+                    if let Some(transform) = transform.as_deref_mut() {
+                        transform.scale = keyframes.sample(t);
+                    }
+                }
+                Track::Rotation(keyframes) => {
+                    if let Some(transform) = transform.as_deref_mut() {
+                        transform.rotation = Quat::from_rotation_z(keyframes.sample(t));
+                    }
+                }
+                Track::FillColor(keyframes) => {
+                    if let Some(fill) = fill.as_deref_mut() {
+                        fill.color = keyframes.sample(t);
+                    }
+                }
+                Track::StrokeColor(keyframes) => {
+                    if let Some(stroke) = stroke.as_deref_mut() {
+                        stroke.color = keyframes.sample(t);
+                    }
+                }
+                Track::Alpha(_) => {}
+            }
+        }
+
+        for track in self.tracks.iter() {
+            if let Track::Alpha(keyframes) = track {
+                let alpha = keyframes.sample(t);
+                if let Some(fill) = fill.as_deref_mut() {
+                    fill.color.set_a(alpha);
+                }
+                if let Some(stroke) = stroke.as_deref_mut() {
+                    stroke.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+fn tween_system(
+    clock: Res<GameClock>,
+    mut query: Query<(
+        &mut Tween,
+        Option<&mut Transform>,
+        Option<&mut Fill>,
+        Option<&mut Stroke>,
+    )>,
+) {
+    for (mut tween, transform, fill, stroke) in query.iter_mut() {
+        if tween.finished() {
+            continue;
+        }
+
+        tween.tick(clock.delta());
+        tween.apply(
+            transform.map(|t| t.into_inner()),
+            fill.map(|f| f.into_inner()),
+            stroke.map(|s| s.into_inner()),
+        );
+    }
+}
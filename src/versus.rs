@@ -0,0 +1,256 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+
+use crate::{
+    collision::CollisionSystemLabel,
+    players::{Player, PlayerCount, MAX_PLAYERS},
+    spawn_screen,
+    state::{GameState, StateVisible},
+    waves::GameMode,
+    Ship,
+};
+
+pub struct VersusPlugin {
+    settings: VersusSettings,
+}
+
+impl VersusPlugin {
+    pub fn new(settings: VersusSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .init_resource::<Standings>()
+            .add_event::<KillEvent>()
+            .add_systems(Startup, kills_hud_setup_system)
+            .add_systems(OnEnter(GameState::MainMenu), standings_reset_system)
+            .add_systems(OnEnter(GameState::RoundResults), results_setup_system)
+            .add_systems(
+                FixedUpdate,
+                (
+                    kill_system.after(CollisionSystemLabel),
+                    round_end_system.after(kill_system),
+                )
+                    .run_if(resource_equals(GameMode::Versus))
+                    .in_set(VersusSystemLabel),
+            )
+            .add_systems(
+                Update,
+                results_system.run_if(in_state(GameState::RoundResults)),
+            )
+            .add_systems(PostUpdate, kills_hud_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct VersusSystemLabel;
+
+#[derive(Debug, Clone, Resource)]
+pub struct VersusSettings {
+    /// Kills that win the round outright.
+    pub kill_limit: u32,
+    /// How long a ship coming back stays harmless and untouchable.
+    pub spawn_protection: Duration,
+    /// Distance from the center of the ring the ships spawn on.
+    pub spawn_radius: f32,
+}
+
+impl Default for VersusSettings {
+    fn default() -> Self {
+        Self {
+            kill_limit: 5,
+            spawn_protection: Duration::from_secs(3),
+            spawn_radius: 200.0,
+        }
+    }
+}
+
+impl VersusSettings {
+    /// Spot of the player on the spawn ring, with the rotation that faces the center.
+    pub fn spawn_point(&self, player: Player, count: PlayerCount) -> (Vec2, f32) {
+        let angle = PI + 2.0 * PI * *player as f32 / *count as f32;
+        (Vec2::from_angle(angle) * self.spawn_radius, angle + PI)
+    }
+
+    /// Spot on the spawn ring farthest from every other ship, the player's own when no one else
+    /// is around.
+    pub fn respawn_point(
+        &self,
+        player: Player,
+        count: PlayerCount,
+        others: &[Vec2],
+    ) -> (Vec2, f32) {
+        let clearance = |(point, _): &(Vec2, f32)| {
+            others
+                .iter()
+                .map(|other| other.distance(*point))
+                .fold(f32::INFINITY, f32::min)
+        };
+
+        count
+            .players()
+            .map(|slot| self.spawn_point(slot, count))
+            .max_by(|a, b| clearance(a).total_cmp(&clearance(b)))
+            .filter(|_| !others.is_empty())
+            .unwrap_or_else(|| self.spawn_point(player, count))
+    }
+}
+
+/// A ship of `victim` was destroyed, by a shot of `killer` if anyone.
+#[derive(Debug, Event)]
+pub struct KillEvent {
+    pub victim: Player,
+    pub killer: Option<Player>,
+}
+
+/// Kills and deaths of the current round, and rounds won since the match started.
+#[derive(Debug, Default, Resource)]
+pub struct Standings {
+    kills: [u32; MAX_PLAYERS],
+    deaths: [u32; MAX_PLAYERS],
+    wins: [u32; MAX_PLAYERS],
+    winner: Option<Player>,
+}
+
+impl Standings {
+    pub fn reset_round(&mut self) {
+        self.kills = [0; MAX_PLAYERS];
+        self.deaths = [0; MAX_PLAYERS];
+        self.winner = None;
+    }
+}
+
+#[derive(Debug, Component)]
+struct KillsText;
+
+fn kill_system(mut kills: EventReader<KillEvent>, mut standings: ResMut<Standings>) {
+    for KillEvent { victim, killer } in kills.iter() {
+        standings.deaths[**victim] += 1;
+        if let Some(killer) = killer.filter(|killer| killer != victim) {
+            standings.kills[*killer] += 1;
+        }
+    }
+}
+
+fn round_end_system(
+    settings: Res<VersusSettings>,
+    count: Res<PlayerCount>,
+    mut standings: ResMut<Standings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    ships: Query<&Player, With<Ship>>,
+) {
+    let champion = count
+        .players()
+        .find(|player| standings.kills[**player] >= settings.kill_limit);
+
+    let winner = match (champion, ships.iter().len()) {
+        (Some(champion), _) => Some(champion),
+        (None, 0) => None,
+        (None, 1) => ships.iter().next().copied(),
+        _ => return,
+    };
+
+    if let Some(winner) = winner {
+        standings.wins[*winner] += 1;
+    }
+    standings.winner = winner;
+    next_state.set(GameState::RoundResults);
+}
+
+fn standings_reset_system(mut standings: ResMut<Standings>) {
+    *standings = Standings::default();
+}
+
+fn results_setup_system(
+    mut commands: Commands,
+    count: Res<PlayerCount>,
+    standings: Res<Standings>,
+) {
+    let headline = match standings.winner {
+        Some(winner) => format!("{} WINS", winner.label()),
+        None => "DRAW".to_string(),
+    };
+
+    let table = count
+        .players()
+        .map(|player| {
+            format!(
+                "{}  KILLS {:>2}  DEATHS {:>2}  WINS {:>2}",
+                player.label(),
+                standings.kills[*player],
+                standings.deaths[*player],
+                standings.wins[*player]
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![("ROUND OVER", 64.0), (headline.as_str(), 32.0)];
+    lines.extend(table.iter().map(|line| (line.as_str(), 20.0)));
+    lines.push(("Press Enter for another round", 24.0));
+    lines.push(("Press Escape to return to the menu", 24.0));
+
+    spawn_screen(&mut commands, GameState::RoundResults, &lines);
+}
+
+fn results_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        next_state.set(GameState::Playing);
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn kills_hud_setup_system(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_sections((0..MAX_PLAYERS).map(|player| {
+            TextSection::new(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: Player(player).color(),
+                    ..Default::default()
+                },
+            )
+        }))
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(34.0),
+            right: Val::Px(16.0),
+            ..Default::default()
+        }),
+        KillsText,
+        StateVisible(GameState::Playing),
+    ));
+}
+
+fn kills_hud_system(
+    mode: Res<GameMode>,
+    count: Res<PlayerCount>,
+    standings: Res<Standings>,
+    mut query: Query<&mut Text, With<KillsText>>,
+) {
+    for mut text in query.iter_mut() {
+        for (player, section) in text.sections.iter_mut().enumerate() {
+            let value = match *mode {
+                GameMode::Versus if player < **count => {
+                    format!(
+                        "  {} KILLS {}",
+                        Player(player).label(),
+                        standings.kills[player]
+                    )
+                }
+                _ => String::new(),
+            };
+            if section.value != value {
+                section.value = value;
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*, window::PrimaryWindow};
+use rand::Rng;
+
+use crate::{
+    boundary::Bounding,
+    random::{RandomStream, StreamLabel},
+    state::{GameState, StateVisible},
+    Asteroid, AsteroidSizes, AsteroidSpawnEvent, Ship,
+};
+
+pub struct WavePlugin {
+    mode: GameMode,
+    settings: WaveSettings,
+}
+
+impl WavePlugin {
+    pub fn new(mode: GameMode, settings: WaveSettings) -> Self {
+        Self { mode, settings }
+    }
+}
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.mode)
+            .insert_resource(self.settings.clone())
+            .init_resource::<Wave>()
+            .add_systems(Startup, wave_hud_setup_system)
+            .add_systems(
+                FixedUpdate,
+                wave_system
+                    .run_if(resource_equals(GameMode::Waves))
+                    .in_set(WaveSystemLabel),
+            )
+            .add_systems(PostUpdate, wave_hud_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet, ScheduleLabel)]
+pub struct WaveSystemLabel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum GameMode {
+    /// Fixed waves of big asteroids, the next one starting once the field is cleared.
+    Waves,
+    /// Asteroids of every size keep drifting in from the edges, forever.
+    Endless,
+    /// Ships fight each other among drifting asteroids until one is left standing.
+    Versus,
+}
+
+impl GameMode {
+    /// Reads the mode from an `--endless` argument, defaulting to waves.
+    pub fn from_env() -> Self {
+        if std::env::args().any(|arg| arg == "--endless") {
+            GameMode::Endless
+        } else {
+            GameMode::Waves
+        }
+    }
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct WaveSettings {
+    pub asteroids: u32,
+    pub extra_asteroids_per_wave: u32,
+    pub max_asteroids: u32,
+    /// Asteroid speed added for every wave after the first, as a fraction of the base speed.
+    pub speed_increase_per_wave: f32,
+    /// Minimum distance between the ship and a newly placed asteroid.
+    pub safe_distance: f32,
+    /// How long the field has to stay empty before the next wave starts.
+    pub delay: Duration,
+}
+
+impl Default for WaveSettings {
+    fn default() -> Self {
+        Self {
+            asteroids: 4,
+            extra_asteroids_per_wave: 2,
+            max_asteroids: 11,
+            speed_increase_per_wave: 0.1,
+            safe_distance: 150.0,
+            delay: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct Wave {
+    number: u32,
+    countdown: Timer,
+}
+
+impl Wave {
+    /// Multiplier applied to the speed of every asteroid of the current wave.
+    pub fn speed_scale(&self, settings: &WaveSettings) -> f32 {
+        1.0 + settings.speed_increase_per_wave * self.number.saturating_sub(1) as f32
+    }
+
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+struct WavePlacement;
+
+impl StreamLabel for WavePlacement {
+    const LABEL: &'static str = "wave_placement";
+}
+
+#[derive(Debug, Component)]
+struct WaveText;
+
+fn wave_system(
+    fixed_time: Res<FixedTime>,
+    settings: Res<WaveSettings>,
+    asteroid_sizes: Res<AsteroidSizes>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut rng: Local<RandomStream<WavePlacement>>,
+    mut wave: ResMut<Wave>,
+    mut spawns: EventWriter<AsteroidSpawnEvent>,
+    asteroids: Query<(), With<Asteroid>>,
+    ships: Query<&Transform, With<Ship>>,
+) {
+    if !asteroids.is_empty() {
+        wave.countdown = Timer::new(settings.delay, TimerMode::Once);
+        return;
+    }
+
+    if wave.countdown.duration().is_zero() {
+        wave.countdown = Timer::new(settings.delay, TimerMode::Once);
+    }
+
+    wave.countdown.tick(fixed_time.period);
+    if !wave.countdown.just_finished() {
+        return;
+    }
+
+    wave.number += 1;
+
+    let Ok(window) = primary_window.get_single() else {
+        return;
+    };
+    let w = window.width() / 2.0;
+    let h = window.height() / 2.0;
+
+    let count = (settings.asteroids + settings.extra_asteroids_per_wave * (wave.number - 1))
+        .min(settings.max_asteroids);
+
+    for _ in 0..count {
+        let mut position = Vec2::new(rng.gen_range(-w..w), rng.gen_range(-h..h));
+
+        for _ in 0..16 {
+            let safe = ships.iter().all(|ship| {
+                ship.translation.truncate().distance(position) >= settings.safe_distance
+            });
+            if safe {
+                break;
+            }
+            position = Vec2::new(rng.gen_range(-w..w), rng.gen_range(-h..h));
+        }
+
+        let radius = rng.gen_range(asteroid_sizes.big.clone());
+        spawns.send(AsteroidSpawnEvent(
+            position,
+            Bounding::from_radius(radius),
+            None,
+        ));
+    }
+}
+
+fn wave_hud_setup_system(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..Default::default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(16.0),
+            ..Default::default()
+        }),
+        WaveText,
+        StateVisible(GameState::Playing),
+    ));
+}
+
+fn wave_hud_system(wave: Res<Wave>, mut query: Query<&mut Text, With<WaveText>>) {
+    for mut text in query.iter_mut() {
+        let value = match wave.number {
+            0 => String::new(),
+            number => format!("WAVE {number}"),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}